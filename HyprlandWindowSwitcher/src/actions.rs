@@ -0,0 +1,163 @@
+// Custom actions: shell commands from the config, bound to keys and run on
+// windows with placeholders filled in from the window
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::model::Window;
+
+// Descendants followed when looking for the process a terminal runs
+const MAX_PROCESS_DEPTH: usize = 8;
+
+/// Whether `key` is the key named in the config, like "S" or "ctrl+d"
+pub fn key_matches(spec: &str, key: KeyEvent) -> bool {
+    let (ctrl, name) = match spec.strip_prefix("ctrl+") {
+        Some(name) => (true, name),
+        None => (false, spec),
+    };
+    let mut chars = name.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return false;
+    };
+    let KeyCode::Char(pressed) = key.code else {
+        return false;
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) != ctrl {
+        return false;
+    }
+    // Terminals report ctrl+letter in either case
+    if ctrl {
+        pressed.eq_ignore_ascii_case(&c)
+    } else {
+        pressed == c
+    }
+}
+
+/// `text` as one shell word
+pub fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Working directory of the window's process. For a terminal that is the
+/// directory of the shell or program running in it, found by following the
+/// newest child process down.
+pub fn process_cwd(pid: i64) -> Option<PathBuf> {
+    if pid <= 0 {
+        return None;
+    }
+    let mut pid = pid.to_string();
+    for _ in 0..MAX_PROCESS_DEPTH {
+        let children = fs::read_to_string(format!("/proc/{}/task/{}/children", pid, pid)).unwrap_or_default();
+        match children.split_whitespace().last() {
+            Some(child) => pid = child.to_string(),
+            None => break,
+        }
+    }
+    fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+/// `template` with {address}, {pid}, {class}, {title}, {workspace} and
+/// {cwd} replaced by the window's values, each quoted for the shell so a
+/// title can never run as part of the command
+pub fn expand(template: &str, win: &Window, cwd: Option<&str>) -> String {
+    let values = [
+        ("{address}", win.id.clone()),
+        ("{pid}", win.pid.to_string()),
+        ("{class}", win.class.clone()),
+        ("{title}", win.title.clone()),
+        ("{workspace}", win.workspace.clone()),
+        ("{cwd}", cwd.unwrap_or_default().to_string()),
+    ];
+    let mut command = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        command.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match values.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, value)) => {
+                command.push_str(&quote(value));
+                rest = &rest[name.len()..];
+            }
+            None => {
+                command.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    command.push_str(rest);
+    command
+}
+
+/// Run `command` with `sh -c` and wait for it, returning whether it exited
+/// successfully along with what it printed to stdout and stderr
+pub fn run(command: &str) -> (io::Result<()>, String) {
+    let output = match Command::new("sh").arg("-c").arg(command).stdin(Stdio::null()).output() {
+        Ok(output) => output,
+        Err(e) => return (Err(e), String::new()),
+    };
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    let result = if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(match output.status.code() {
+            Some(code) => format!("exit status {}", code),
+            None => "killed by a signal".to_string(),
+        }))
+    };
+    (result, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(title: &str) -> Window {
+        Window {
+            pid: 4242,
+            ..Window::new("0xabc", "kitty", title, 3)
+        }
+    }
+
+    #[test]
+    fn placeholders_become_quoted_words() {
+        let command = expand("notify-send {class} {title} --pid={pid} {cwd}", &window("vim"), Some("/tmp/a b"));
+        assert_eq!(command, "notify-send 'kitty' 'vim' --pid='4242' '/tmp/a b'");
+    }
+
+    #[test]
+    fn titles_cannot_break_out_of_their_quotes() {
+        let command = expand("echo {title}", &window("it's $(rm -rf ~)"), None);
+        assert_eq!(command, r"echo 'it'\''s $(rm -rf ~)'");
+        let (result, output) = run(&command);
+        assert!(result.is_ok());
+        assert_eq!(output, "it's $(rm -rf ~)\n");
+    }
+
+    #[test]
+    fn unknown_braces_are_kept() {
+        assert_eq!(expand("awk '{print $1}' {x", &window("t"), None), "awk '{print $1}' {x");
+    }
+
+    #[test]
+    fn keys_with_and_without_ctrl() {
+        let plain = KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT);
+        let ctrl = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert!(key_matches("S", plain));
+        assert!(!key_matches("s", plain));
+        assert!(key_matches("ctrl+d", ctrl));
+        assert!(!key_matches("d", ctrl));
+        assert!(!key_matches("ctrl+", ctrl));
+    }
+
+    #[test]
+    fn failing_commands_keep_their_output() {
+        let (result, output) = run("echo oops >&2; exit 3");
+        assert_eq!(result.unwrap_err().to_string(), "exit status 3");
+        assert_eq!(output, "oops\n");
+    }
+}
@@ -0,0 +1,1620 @@
+// App state and input handling: filtering, selection, prompts and the
+// actions keys and mouse trigger
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+
+use crate::actions;
+use crate::config::{Config, CustomAction, OutputMode};
+use crate::daemon::Snapshot;
+use crate::events::{self, HyprEvent};
+use crate::grid::GridLayout;
+use crate::history::History;
+use crate::launcher::{self, Launcher};
+use crate::layout::{hit_target, hit_test, split_screen, window_at, ListLayout, MinimapLayout};
+use crate::marks::{self, Marks};
+use crate::model::{fuzzy_score, parse_tag_edits, split_tag_filter, unread_count, Monitor, Window, Workspace};
+use crate::popup;
+use crate::rewrite::Rewriter;
+use crate::rules::{self, RuleBuilder};
+use crate::ui::Theme;
+use crate::worker::{Done, Job, Worker};
+
+// Ranking boost for often and recently selected windows. Logarithmic so a
+// frequently used window wins between similar matches without burying a much
+// better match for the typed text
+fn frecency_bonus(frecency: f64) -> i64 {
+    (frecency.ln_1p() * 50.0) as i64
+}
+
+// Whether peek mode may pin the host: it floats and is not pinned already
+fn pinnable(windows: &[Window], host: Option<&str>) -> bool {
+    windows
+        .iter()
+        .find(|w| Some(w.id.as_str()) == host)
+        .is_some_and(|w| w.floating && !w.pinned)
+}
+
+// Desktop entries offered when the filter matches no window
+const MAX_DESKTOP_RESULTS: usize = 12;
+
+// Two clicks on the same card within this interval count as a double-click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+// Focus changes caused by our own dispatches are not a reason to close
+const FOCUS_GRACE: Duration = Duration::from_millis(500);
+
+pub struct Options {
+    // Select the card under the pointer as the mouse moves
+    pub hover_select: bool,
+    // Start with the window list restricted to the focused monitor / active workspace
+    pub this_monitor: bool,
+    pub this_workspace: bool,
+    // Quit when another window gets focus
+    pub auto_close: bool,
+    // Focus the highlighted window while navigating
+    pub peek: bool,
+    // List windows asking for attention before the others
+    pub urgent_first: bool,
+    // Draw a list of this many lines below the prompt instead of taking over the screen
+    pub height: Option<u16>,
+    // Start in the one-line-per-window list instead of the card grid
+    pub list: bool,
+}
+
+// An inline list needs the prompt line and at least one row
+const MIN_INLINE_HEIGHT: u16 = 2;
+
+// Lines Page Up/Down move the output pager by
+const PAGER_PAGE: usize = 10;
+
+impl Options {
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        let mut options = Options {
+            hover_select: true,
+            this_monitor: false,
+            this_workspace: false,
+            auto_close: true,
+            peek: false,
+            urgent_first: false,
+            height: None,
+            list: false,
+        };
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--height" => match args.next().and_then(|n| n.parse::<u16>().ok()) {
+                    Some(lines) => options.height = Some(lines.max(MIN_INLINE_HEIGHT)),
+                    None => eprintln!("--height expects a number of lines"),
+                },
+                "--no-hover-select" => options.hover_select = false,
+                "--this-monitor" => options.this_monitor = true,
+                "--this-workspace" => options.this_workspace = true,
+                "--stay-open" => options.auto_close = false,
+                "--peek" => options.peek = true,
+                "--urgent-first" => options.urgent_first = true,
+                "--list" => options.list = true,
+                _ => eprintln!("ignoring unknown argument: {}", arg),
+            }
+        }
+        options
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Grid,
+    List,
+    Minimap,
+    Workspaces,
+    Monitors,
+}
+
+// Columns of the list view that its headers sort by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Class,
+    Title,
+    Workspace,
+    State,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 4] = [SortColumn::Class, SortColumn::Title, SortColumn::Workspace, SortColumn::State];
+
+    pub fn heading(self) -> &'static str {
+        match self {
+            SortColumn::Class => "Class",
+            SortColumn::Title => "Title",
+            SortColumn::Workspace => "Workspace",
+            SortColumn::State => "State",
+        }
+    }
+}
+
+// Order of the window list chosen from the list headers; without one windows
+// are ranked by filter match and frecency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+// Text entry or confirmation shown on top of the current view
+pub enum PromptKind {
+    RenameWorkspace(usize),
+    CreateWorkspace,
+    CloseWorkspaceWindows(usize),
+    // Tags to toggle on the window with this address
+    Tags(String),
+}
+
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+// What the pointer is over: a window card or a workspace slot in the strip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitTarget {
+    Card(usize),
+    Workspace(usize),
+}
+
+// A card being dragged with the left mouse button
+pub struct Drag {
+    pub source: usize,
+    pub over: Option<HitTarget>,
+    pub moved: bool,
+}
+
+/// Card labels for an action while it runs and once Hyprland confirmed it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Verb {
+    pub pending: &'static str,
+    pub done: &'static str,
+}
+
+const FOCUS: Verb = Verb { pending: "focusing", done: "focused" };
+const CLOSE: Verb = Verb { pending: "closing", done: "closed" };
+const MOVE: Verb = Verb { pending: "moving", done: "moved" };
+const SWAP: Verb = Verb { pending: "swapping", done: "swapped" };
+const TAG: Verb = Verb { pending: "tagging", done: "tagged" };
+const RULE: Verb = Verb { pending: "reloading", done: "rule added" };
+const RUN: Verb = Verb { pending: "running", done: "done" };
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionState {
+    Pending(Verb),
+    Done(Verb),
+    Failed(String),
+}
+
+// What happens once a job has come back from the worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Then {
+    Stay,
+    // Apply the window model the worker queried after the job
+    Refresh,
+    Quit,
+    // Quit if Hyprland accepted the job, else stay and show the error
+    QuitOnSuccess,
+    // Show the output of custom action n the way its config asks for
+    Report(usize),
+}
+
+// Output of custom actions in a scrollable popup
+pub struct Pager {
+    pub title: String,
+    pub lines: Vec<String>,
+    // First line shown
+    pub scroll: usize,
+}
+
+// A job handed to the worker, with the window card showing its progress
+struct Action {
+    window: Option<(String, Verb)>,
+    then: Then,
+}
+
+pub struct App {
+    pub running: bool,
+    pub windows: Vec<Window>,
+    // Indices into `windows` that pass the active filters, in display order
+    pub visible: Vec<usize>,
+    pub filter_monitor: bool,
+    pub filter_workspace: bool,
+    // Text typed after `/`, fuzzy-matched against class and title
+    pub filter_text: String,
+    pub filtering: bool,
+    // Favourites that are not running, then desktop entries when no window matches
+    pub launch_items: Vec<Launcher>,
+    pub favourites: Vec<Launcher>,
+    // Loaded on first use, scanning the application directories is comparatively slow
+    pub desktop_entries: Option<Vec<Launcher>>,
+    pub workspaces: Vec<Workspace>,
+    pub monitors: Vec<Monitor>,
+    pub active_workspace: Option<i64>,
+    pub view: View,
+    pub selected_index: usize,
+    pub workspace_index: usize,
+    pub monitor_index: usize,
+    pub prompt: Option<Prompt>,
+    // Details popup for the selected window, closed by any key
+    pub details: bool,
+    // Window rule being put together for the selected window, see rules.rs
+    pub rule_builder: Option<RuleBuilder>,
+    // Config files rules can be appended to, and whether to reload by default
+    pub rule_files: Vec<PathBuf>,
+    pub reload_rules: bool,
+    pub custom_actions: Vec<CustomAction>,
+    // Addresses of the windows picked with Space for the next custom action
+    pub picked: HashSet<String>,
+    // One-line result of the last custom action, Err when it failed; cleared by the next key
+    pub toast: Option<Result<String, String>>,
+    pub pager: Option<Pager>,
+    pub marks: Marks,
+    pub history: History,
+    // First key of a two-key command (`m` to set a mark, `'` to jump to one)
+    pub pending_key: Option<char>,
+    pub theme: Theme,
+    pub rewriter: Rewriter,
+    pub hover_select: bool,
+    // Drawn as a compact list in an inline viewport, see Options::height
+    pub inline: bool,
+    // What was picked, printed on exit in inline mode: the window as
+    // "address<TAB>class<TAB>title", or the command of a launched app
+    pub chosen: Option<String>,
+    // Address of the terminal window we run in, left out of the window list
+    pub host: Option<String>,
+    pub auto_close: bool,
+    // Focus events are ignored until then, see FOCUS_GRACE
+    pub ignore_focus_until: Option<Instant>,
+    // Window that had focus before the switcher started, refocused on cancel
+    pub original: Option<String>,
+    pub peek: bool,
+    // Last window brought forward by peek mode
+    pub peeked: Option<String>,
+    // Whether peek mode pinned the host terminal and has to unpin it again
+    pub pinned_host: bool,
+    // Whether the host floats and is not pinned yet, from the last snapshot;
+    // peek mode pins only such a host
+    pub host_pinnable: bool,
+    // Addresses of windows that raised an urgent event since they last had focus
+    pub urgent: HashSet<String>,
+    pub urgent_first: bool,
+    // Card index and time of the last left click, for double-click detection
+    pub last_click: Option<(usize, Instant)>,
+    pub drag: Option<Drag>,
+    // Last grid laid out, see App::grid_layout
+    pub grid_layout: RefCell<Option<Rc<GridLayout>>>,
+    pub sort: Option<Sort>,
+    // First row shown by the list view, moved only to keep the selection in sight
+    pub list_scroll: Cell<usize>,
+    // Runs compositor calls off the UI thread; detached until main attaches one
+    pub worker: Worker,
+    // Jobs handed to the worker that have not come back yet, by id
+    jobs: HashMap<u64, Action>,
+    // Progress of the latest action on each window, by address, for its card
+    pub actions: HashMap<String, ActionState>,
+}
+
+impl App {
+    pub fn new(options: &Options, config: &Config, snapshot: Snapshot) -> Self {
+        let host = popup::find_host(&snapshot.windows);
+        App::with_state(options, config, snapshot, host, Marks::load(), History::load())
+    }
+
+    /// App over a given snapshot, host window and saved state, without
+    /// asking Hyprland or reading the state directory
+    pub fn with_state(
+        options: &Options,
+        config: &Config,
+        snapshot: Snapshot,
+        host: Option<String>,
+        marks: Marks,
+        history: History,
+    ) -> Self {
+        let mut windows = snapshot.windows;
+        let host_pinnable = pinnable(&windows, host.as_deref());
+        windows.retain(|w| Some(&w.id) != host.as_ref());
+        let original = windows.iter().min_by_key(|w| w.focus_history).map(|w| w.id.clone());
+        let mut app = App {
+            running: true,
+            windows,
+            visible: Vec::new(),
+            filter_monitor: options.this_monitor,
+            filter_workspace: options.this_workspace,
+            filter_text: String::new(),
+            // Inline mode reads like fzf: typing goes straight into the filter
+            filtering: options.height.is_some(),
+            launch_items: Vec::new(),
+            favourites: config.favourites.iter().map(Launcher::from_favourite).collect(),
+            desktop_entries: None,
+            workspaces: snapshot.workspaces,
+            monitors: snapshot.monitors,
+            active_workspace: snapshot.active_workspace,
+            view: if options.list { View::List } else { View::Grid },
+            selected_index: 0,
+            workspace_index: 0,
+            monitor_index: 0,
+            prompt: None,
+            details: false,
+            rule_builder: None,
+            rule_files: config.rules.files(),
+            reload_rules: config.rules.reload,
+            custom_actions: config.actions.clone(),
+            picked: HashSet::new(),
+            toast: None,
+            pager: None,
+            marks,
+            history,
+            pending_key: None,
+            theme: Theme::dracula(),
+            rewriter: Rewriter::new(config),
+            hover_select: options.hover_select,
+            inline: options.height.is_some(),
+            chosen: None,
+            host,
+            auto_close: options.auto_close,
+            ignore_focus_until: None,
+            original,
+            peek: false,
+            peeked: None,
+            pinned_host: false,
+            host_pinnable,
+            urgent: snapshot.urgent.into_iter().collect(),
+            urgent_first: options.urgent_first,
+            last_click: None,
+            drag: None,
+            grid_layout: RefCell::new(None),
+            sort: None,
+            list_scroll: Cell::new(0),
+            worker: Worker::detached(),
+            jobs: HashMap::new(),
+            actions: HashMap::new(),
+        };
+        let warnings: Vec<&str> = config.warnings.iter().chain(&app.rewriter.warnings).map(String::as_str).collect();
+        if !warnings.is_empty() {
+            app.toast = Some(Err(warnings.join(" • ")));
+        }
+        app.apply_filters();
+        app
+    }
+
+    // Window shown at a position of the filtered list
+    pub fn shown(&self, position: usize) -> Option<&Window> {
+        self.visible.get(position).and_then(|&i| self.windows.get(i))
+    }
+
+    fn focused_monitor(&self) -> Option<&Monitor> {
+        self.monitors.iter().find(|m| m.focused)
+    }
+
+    /// Rebuild the visible list from the monitor/workspace filters,
+    /// keeping the selection on the same window when it is still shown
+    fn apply_filters(&mut self) {
+        let selected = self.shown(self.selected_index).map(|w| w.id.clone());
+        self.apply_filters_keeping(selected);
+    }
+
+    // Like apply_filters, for when `windows` was replaced and the old
+    // selection is only known by address
+    fn apply_filters_keeping(&mut self, selected: Option<String>) {
+        let monitor = self.focused_monitor().map(|m| m.id);
+        let workspace = self.active_workspace;
+
+        let filter_text = self.filter_text.clone();
+        let (tags, pattern) = split_tag_filter(&filter_text);
+        let mut scored: Vec<(usize, i64)> = (0..self.windows.len())
+            .filter(|&i| {
+                let win = &self.windows[i];
+                (!self.filter_monitor || monitor == Some(win.monitor))
+                    && (!self.filter_workspace || workspace == Some(win.workspace_id))
+                    && tags.iter().all(|tag| win.has_tag(tag))
+            })
+            .filter_map(|i| {
+                let win = &self.windows[i];
+                let score = fuzzy_score(&pattern, &self.search_text(win))?;
+                Some((i, score + frecency_bonus(self.history.score(&win.class, &win.title))))
+            })
+            .collect();
+        // Stable, so windows never selected before keep Hyprland's order
+        scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        if self.urgent_first {
+            scored.sort_by_key(|&(i, _)| !self.needs_attention(&self.windows[i]));
+        }
+        if let Some(sort) = self.sort {
+            scored.sort_by_cached_key(|&(i, _)| self.sort_key(&self.windows[i], sort.column));
+            if sort.descending {
+                scored.reverse();
+            }
+        }
+        self.visible = scored.into_iter().map(|(i, _)| i).collect();
+        // Launchers have no tags, so a tag filter leaves them out
+        self.launch_items = if tags.is_empty() {
+            self.matching_launchers(&pattern)
+        } else {
+            Vec::new()
+        };
+
+        let position = selected.and_then(|id| self.visible.iter().position(|&i| self.windows[i].id == id));
+        if let Some(pos) = position {
+            self.selected_index = pos;
+        } else if self.selected_index >= self.visible.len() {
+            self.selected_index = self.visible.len().saturating_sub(1);
+        }
+    }
+
+    // What a list column sorts a window by
+    fn sort_key(&self, win: &Window, column: SortColumn) -> (String, i64) {
+        match column {
+            SortColumn::Class => (self.rewriter.class_name(&win.class).to_lowercase(), 0),
+            SortColumn::Title => (self.rewriter.title(&win.class, &win.title).to_lowercase(), 0),
+            SortColumn::Workspace => (String::new(), win.workspace_id),
+            // Plain windows first, then the ones with more pressing flags
+            SortColumn::State => {
+                let flags = [win.hidden, win.pinned, win.floating, win.fullscreen, self.needs_attention(win)];
+                let rank = flags.iter().enumerate().filter(|(_, on)| **on).map(|(bit, _)| 1 << bit).sum();
+                (String::new(), rank)
+            }
+        }
+    }
+
+    /// Header click: sort by `column`, then reverse it, then go back to ranking
+    pub fn sort_by(&mut self, column: SortColumn) {
+        self.sort = match self.sort {
+            Some(Sort { column: current, descending: false }) if current == column => {
+                Some(Sort { column, descending: true })
+            }
+            Some(Sort { column: current, descending: true }) if current == column => None,
+            _ => Some(Sort { column, descending: false }),
+        };
+        self.apply_filters();
+    }
+
+    // `s`: sort by the next column, ending back at ranking
+    fn cycle_sort(&mut self) {
+        let next = match self.sort {
+            None => Some(SortColumn::ALL[0]),
+            Some(sort) => SortColumn::ALL
+                .iter()
+                .position(|&c| c == sort.column)
+                .and_then(|i| SortColumn::ALL.get(i + 1))
+                .copied(),
+        };
+        self.sort = next.map(|column| Sort { column, descending: false });
+        self.apply_filters();
+    }
+
+    // Text the filter is matched against: the raw class and title, plus the class alias
+    pub fn search_text(&self, win: &Window) -> String {
+        let name = self.rewriter.class_name(&win.class);
+        if name == win.class {
+            format!("{} {}", win.class, win.title)
+        } else {
+            format!("{} {} {}", name, win.class, win.title)
+        }
+    }
+
+    pub fn needs_attention(&self, win: &Window) -> bool {
+        self.urgent.contains(&win.id) || unread_count(&win.title).is_some()
+    }
+
+    // Card badge for urgent windows and unread counters
+    pub fn attention_badge(&self, win: &Window) -> Option<String> {
+        match (self.urgent.contains(&win.id), unread_count(&win.title)) {
+            (true, Some(n)) => Some(format!("󰂚 urgent • {} unread", n)),
+            (true, None) => Some("󰂚 urgent".to_string()),
+            (false, Some(n)) => Some(format!("󰍡 {} unread", n)),
+            (false, None) => None,
+        }
+    }
+
+    /// Move the selection to the next window needing attention, wrapping around
+    fn select_next_urgent(&mut self) {
+        let count = self.visible.len();
+        let next = (1..=count)
+            .map(|step| (self.selected_index + step) % count)
+            .find(|&pos| self.shown(pos).is_some_and(|w| self.needs_attention(w)));
+        if let Some(pos) = next {
+            self.selected_index = pos;
+        }
+    }
+
+    // Header suffix naming the active filters, e.g. " on DP-1, workspace 3"
+    pub fn filter_description(&self) -> String {
+        let mut parts = Vec::new();
+        if self.filter_monitor {
+            let name = self.focused_monitor().map(|m| m.name.as_str()).unwrap_or("?");
+            parts.push(name.to_string());
+        }
+        if self.filter_workspace {
+            let name = self
+                .workspaces
+                .iter()
+                .find(|ws| Some(ws.id) == self.active_workspace)
+                .map(|ws| ws.name.as_str())
+                .unwrap_or("?");
+            parts.push(format!("workspace {}", name));
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" on {}", parts.join(", "))
+        }
+    }
+
+    /// Ghost cards for favourites that are not running, falling back to
+    /// desktop entries when the filter text matches no window at all
+    fn matching_launchers(&mut self, pattern: &str) -> Vec<Launcher> {
+        let running = |class: &str| {
+            self.windows
+                .iter()
+                .any(|w| w.class.eq_ignore_ascii_case(class))
+        };
+        let mut scored: Vec<(Launcher, i64)> = self
+            .favourites
+            .iter()
+            .filter(|f| !running(&f.class))
+            .filter_map(|f| {
+                let score = fuzzy_score(pattern, &f.search_text())?;
+                Some((f.clone(), score + frecency_bonus(self.history.score(&f.class, ""))))
+            })
+            .collect();
+
+        if !pattern.is_empty() && self.visible.is_empty() {
+            let entries = self.desktop_entries.get_or_insert_with(launcher::desktop_entries);
+            let mut found: Vec<(Launcher, i64)> = entries
+                .iter()
+                .filter(|e| !scored.iter().any(|(f, _)| f.command == e.command))
+                .filter_map(|e| fuzzy_score(pattern, &e.search_text()).map(|score| (e.clone(), score)))
+                .collect();
+            found.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+            found.truncate(MAX_DESKTOP_RESULTS);
+            scored.extend(found);
+        }
+
+        scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(launcher, _)| launcher).collect()
+    }
+
+    fn selected_launcher(&self) -> Option<&Launcher> {
+        self.selected_index
+            .checked_sub(self.visible.len())
+            .and_then(|i| self.launch_items.get(i))
+    }
+
+    fn set_filter_text(&mut self, text: String) {
+        self.filter_text = text;
+        self.apply_filters();
+        // The best match is first, keep it under the cursor while typing
+        self.selected_index = 0;
+    }
+
+    fn toggle_monitor_filter(&mut self) {
+        self.filter_monitor = !self.filter_monitor;
+        self.apply_filters();
+    }
+
+    fn toggle_workspace_filter(&mut self) {
+        self.filter_workspace = !self.filter_workspace;
+        self.apply_filters();
+    }
+
+    pub fn get_app_icon(&self, class: &str) -> &'static str {
+        match class.to_lowercase().as_str() {
+            "firefox" | "firefox-esr" => "󰈹",
+            "google-chrome" | "chromium" => "󰊯",
+            "code" | "code-oss" | "vscodium" => "󰨞",
+            "kitty" | "alacritty" | "wezterm" | "foot" => "󰆍",
+            "thunar" | "nautilus" | "dolphin" | "pcmanfm" => "󰉋",
+            "discord" => "󰙯",
+            "slack" => "󰒱",
+            "telegram" | "telegram-desktop" => "󰔿",
+            "spotify" => "󰓇",
+            "vlc" | "mpv" => "󰕼",
+            "gimp" => "󰏘",
+            "blender" => "󰂫",
+            "libreoffice" => "󰈙",
+            "steam" => "󰓓",
+            "obsidian" => "󱓷",
+            "notion" => "󰈚",
+            _ => "󰣆",
+        }
+    }
+
+    /// Grid for `count` cards in `area`, laid out once and shared by
+    /// rendering, hit testing and key handling until either changes
+    pub fn grid_layout(&self, count: usize, area: Rect) -> Rc<GridLayout> {
+        let mut cached = self.grid_layout.borrow_mut();
+        if let Some(grid) = cached.as_ref().filter(|g| g.count == count && g.area == area) {
+            return Rc::clone(grid);
+        }
+        let grid = Rc::new(GridLayout::new(count, area));
+        *cached = Some(Rc::clone(&grid));
+        grid
+    }
+
+    // Number of cards in the current view
+    pub fn item_count(&self) -> usize {
+        match self.view {
+            View::Grid | View::List => self.visible.len() + self.launch_items.len(),
+            View::Minimap => self.visible.len(),
+            View::Workspaces => self.workspaces.len(),
+            View::Monitors => self.monitors.len(),
+        }
+    }
+
+    // Selected card in the current view
+    fn selection_mut(&mut self) -> &mut usize {
+        match self.view {
+            View::Grid | View::List | View::Minimap => &mut self.selected_index,
+            View::Workspaces => &mut self.workspace_index,
+            View::Monitors => &mut self.monitor_index,
+        }
+    }
+
+    fn select_next(&mut self) {
+        let count = self.item_count();
+        let selected = self.selection_mut();
+        if *selected + 1 < count {
+            *selected += 1;
+        }
+    }
+
+    fn select_previous(&mut self) {
+        let selected = self.selection_mut();
+        *selected = selected.saturating_sub(1);
+    }
+
+    // Arrow key on a card grid laid out in `area`
+    fn select_in_grid(&mut self, area: Rect, step: fn(&GridLayout, usize) -> usize) {
+        let grid = self.grid_layout(self.item_count(), area);
+        let selected = self.selection_mut();
+        *selected = step(&grid, *selected);
+    }
+
+    fn toggle_view(&mut self) {
+        self.view = match self.view {
+            View::Grid => View::List,
+            View::List => View::Minimap,
+            View::Minimap => View::Workspaces,
+            View::Workspaces => View::Monitors,
+            View::Monitors => View::Grid,
+        };
+        self.last_click = None;
+        self.drag = None;
+    }
+
+    // `v`: straight between the card grid and the list
+    fn toggle_list(&mut self) {
+        self.view = if self.view == View::List { View::Grid } else { View::List };
+        self.last_click = None;
+        self.drag = None;
+    }
+
+    fn switch_to_selected_workspace(&mut self) {
+        let Some(ws) = self.workspaces.get(self.workspace_index) else {
+            self.running = false;
+            return;
+        };
+        let job = Job::dispatch(&["workspace", &ws.dispatch_target()]);
+        self.submit(job, None, Then::QuitOnSuccess);
+    }
+
+    fn focus_selected_monitor(&mut self) {
+        let Some(monitor) = self.monitors.get(self.monitor_index) else {
+            self.running = false;
+            return;
+        };
+        let job = Job::dispatch(&["focusmonitor", &monitor.name]);
+        self.submit(job, None, Then::QuitOnSuccess);
+    }
+
+    fn rename_workspace(&mut self, index: usize, name: &str) {
+        let (Some(ws), false) = (self.workspaces.get(index), name.is_empty()) else {
+            return;
+        };
+        let job = Job::dispatch(&["renameworkspace", &ws.id.to_string(), name]);
+        self.submit(job, None, Then::Refresh);
+    }
+
+    fn create_workspace(&mut self, name: &str) {
+        // An empty name takes the first free workspace number
+        let target = if name.is_empty() {
+            "empty".to_string()
+        } else {
+            format!("name:{}", name)
+        };
+        self.submit(Job::dispatch(&["workspace", &target]), None, Then::QuitOnSuccess);
+    }
+
+    fn move_selected_workspace_to_next_monitor(&mut self) {
+        let Some(ws) = self.workspaces.get(self.workspace_index) else {
+            return;
+        };
+        let current = self.monitors.iter().position(|m| m.name == ws.monitor);
+        let next = match current {
+            Some(i) => self.monitors.get((i + 1) % self.monitors.len()),
+            None => self.monitors.first(),
+        };
+        let Some(next) = next else {
+            return;
+        };
+        if next.name == ws.monitor {
+            return;
+        }
+        let job = Job::dispatch(&["moveworkspacetomonitor", &ws.dispatch_target(), &next.name]);
+        self.submit(job, None, Then::Refresh);
+    }
+
+    fn close_workspace_windows(&mut self, index: usize) {
+        let Some(ws) = self.workspaces.get(index) else {
+            return;
+        };
+        let batch = self
+            .windows
+            .iter()
+            .filter(|w| w.workspace_id == ws.id)
+            .map(|w| format!("dispatch closewindow address:{}", w.id))
+            .collect::<Vec<_>>()
+            .join(" ; ");
+        if !batch.is_empty() {
+            self.submit(Job::Batch(batch), None, Then::Refresh);
+        }
+    }
+
+    fn open_prompt(&mut self, kind: PromptKind) {
+        let input = match kind {
+            PromptKind::RenameWorkspace(i) => self
+                .workspaces
+                .get(i)
+                .map(|ws| ws.name.clone())
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        self.prompt = Some(Prompt { kind, input });
+    }
+
+    fn submit_prompt(&mut self) {
+        let Some(prompt) = self.prompt.take() else {
+            return;
+        };
+        let input = prompt.input.trim();
+        match prompt.kind {
+            PromptKind::RenameWorkspace(i) => self.rename_workspace(i, input),
+            PromptKind::CreateWorkspace => self.create_workspace(input),
+            PromptKind::CloseWorkspaceWindows(i) => self.close_workspace_windows(i),
+            PromptKind::Tags(address) => self.toggle_tags(&address, input),
+        }
+    }
+
+    // `R`: start a window rule from the selected window
+    fn open_rule_builder(&mut self) {
+        let Some(win) = self.shown(self.selected_index) else {
+            return;
+        };
+        let monitor = self.monitors.iter().find(|m| m.id == win.monitor);
+        self.rule_builder = Some(RuleBuilder::new(win, monitor, self.rule_files.clone(), self.reload_rules));
+    }
+
+    /// Enter in the rule builder: append the rule, then reload Hyprland if
+    /// asked to. The window's card reports how it went.
+    fn write_rule(&mut self) {
+        // Stay open until there is something to write and somewhere to write it
+        let Some(builder) = self.rule_builder.take_if(|b| !b.lines().is_empty() && b.file().is_some()) else {
+            return;
+        };
+        let Some(path) = builder.file() else {
+            return;
+        };
+        let backup = match rules::append_rules(path, &builder.lines()) {
+            Ok(backup) => backup,
+            Err(e) => {
+                let error = format!("rule not saved: {}", e);
+                self.actions.insert(builder.window, ActionState::Failed(error));
+                return;
+            }
+        };
+        self.toast = Some(Ok(match backup {
+            Some(backup) => format!("Rule appended to {}, backup in {}", path.display(), backup.display()),
+            None => format!("Rule written to new file {}", path.display()),
+        }));
+        if builder.reload {
+            self.submit(Job::Reload, Some((builder.window, RULE)), Then::Refresh);
+        } else {
+            self.actions.insert(builder.window, ActionState::Done(RULE));
+        }
+    }
+
+    // `t`: ask for tags to toggle on the selected window
+    fn open_tags_prompt(&mut self) {
+        if let Some(win) = self.shown(self.selected_index) {
+            self.prompt = Some(Prompt {
+                kind: PromptKind::Tags(win.id.clone()),
+                input: String::new(),
+            });
+        }
+    }
+
+    /// Toggle each tag in `input` on a window; `+tag` and `-tag` only add or
+    /// remove, as with `hyprctl dispatch tagwindow`
+    fn toggle_tags(&mut self, address: &str, input: &str) {
+        let edits = match parse_tag_edits(input) {
+            Ok(edits) => edits,
+            Err(e) => {
+                self.actions.insert(address.to_string(), ActionState::Failed(e));
+                return;
+            }
+        };
+        let batch = edits
+            .iter()
+            .map(|tag| format!("dispatch tagwindow {} address:{}", tag, address))
+            .collect::<Vec<_>>()
+            .join(" ; ");
+        if !batch.is_empty() {
+            self.submit(Job::Batch(batch), Some((address.to_string(), TAG)), Then::Refresh);
+        }
+    }
+
+    /// Select the nearest window in `direction` on the minimap, favouring
+    /// windows that line up with the current one on the other axis
+    fn select_direction(&mut self, direction: Direction) {
+        let map = MinimapLayout::new(self);
+        let Some(current) = map.window_rect(self.selected_index) else {
+            return;
+        };
+        let (cx, cy) = current.center();
+
+        let best = map
+            .windows
+            .iter()
+            .filter(|(i, _)| *i != self.selected_index)
+            .filter_map(|(i, rect)| {
+                let (x, y) = rect.center();
+                let (along, across) = match direction {
+                    Direction::Left => (cx - x, (y - cy).abs()),
+                    Direction::Right => (x - cx, (y - cy).abs()),
+                    Direction::Up => (cy - y, (x - cx).abs()),
+                    Direction::Down => (y - cy, (x - cx).abs()),
+                };
+                (along > 0.0).then_some((*i, along + 2.0 * across))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((i, _)) = best {
+            self.selected_index = i;
+        }
+    }
+
+    fn set_mark(&mut self, key: char) {
+        let Some(&i) = self.visible.get(self.selected_index) else {
+            return;
+        };
+        self.marks.set(key, &self.windows[i]);
+        if let Err(e) = self.marks.save() {
+            self.toast = Some(Err(format!("Mark {} not saved: {}", key, e)));
+        }
+    }
+
+    fn jump_to_mark(&mut self, key: char) {
+        let Some(win) = self.marks.resolve(key, &self.windows) else {
+            return;
+        };
+        let id = win.id.clone();
+        if let Some(pos) = (0..self.visible.len()).find(|&p| self.shown(p).is_some_and(|w| w.id == id)) {
+            self.selected_index = pos;
+            self.focus_selected_window();
+        } else {
+            // Hidden by a filter: focus it directly
+            self.history.record(&win.class, &win.title);
+            let _ = self.history.save();
+            let job = Job::dispatch(&["focuswindow", &format!("address:{}", id)]);
+            self.submit(job, None, Then::QuitOnSuccess);
+        }
+    }
+
+    // Enter on the window grid: focus the window, or launch a ghost card's app
+    fn activate_selected(&mut self) {
+        if let Some(launcher) = self.selected_launcher().cloned() {
+            self.history.record(&launcher.class, "");
+            let _ = self.history.save();
+            self.chosen = Some(launcher.command.clone());
+            self.submit(Job::dispatch(&["exec", &launcher.command]), None, Then::QuitOnSuccess);
+        } else {
+            self.focus_selected_window();
+        }
+    }
+
+    fn focus_selected_window(&mut self) {
+        let Some(win) = self.visible.get(self.selected_index).map(|&i| &self.windows[i]) else {
+            self.running = false;
+            return;
+        };
+        if self.is_pending(win) {
+            return;
+        }
+        self.history.record(&win.class, &win.title);
+        let _ = self.history.save();
+        self.chosen = Some(format!("{}\t{}\t{}", win.id, win.class, win.title));
+        let id = win.id.clone();
+        let job = Job::dispatch(&["focuswindow", &format!("address:{}", id)]);
+        self.submit(job, Some((id, FOCUS)), Then::QuitOnSuccess);
+    }
+
+    fn close_selected_window(&mut self) {
+        let Some(win) = self.shown(self.selected_index) else {
+            return;
+        };
+        if self.is_pending(win) {
+            return;
+        }
+        // The card stays until Hyprland confirms, the refresh then drops it
+        let id = win.id.clone();
+        let job = Job::dispatch(&["closewindow", &format!("address:{}", id)]);
+        self.submit(job, Some((id, CLOSE)), Then::Refresh);
+    }
+
+    fn move_window_to_workspace(&mut self, window_index: usize, workspace_index: usize) {
+        let (Some(win), Some(ws)) = (
+            self.shown(window_index),
+            self.workspaces.get(workspace_index),
+        ) else {
+            return;
+        };
+        if win.workspace_id == ws.id {
+            return;
+        }
+        let id = win.id.clone();
+        let job = Job::dispatch(&[
+            "movetoworkspacesilent",
+            &format!("{},address:{}", ws.dispatch_target(), id),
+        ]);
+        self.selected_index = window_index;
+        self.submit(job, Some((id, MOVE)), Then::Refresh);
+    }
+
+    fn swap_windows(&mut self, source: usize, target: usize) {
+        let (Some(a), Some(b)) = (self.shown(source), self.shown(target)) else {
+            return;
+        };
+        if source == target {
+            return;
+        }
+        // swapwindow acts on the active window, so focus the dragged one first
+        // and hand focus back to the switcher afterwards
+        let mut batch = format!(
+            "dispatch focuswindow address:{} ; dispatch swapwindow address:{}",
+            a.id, b.id
+        );
+        if let Some(host) = &self.host {
+            batch.push_str(&format!(" ; dispatch focuswindow address:{}", host));
+        }
+        let id = a.id.clone();
+        self.ignore_focus_until = Some(Instant::now() + FOCUS_GRACE);
+        self.selected_index = source;
+        self.submit(Job::Batch(batch), Some((id, SWAP)), Then::Refresh);
+    }
+
+    /// Live updates from the event socket while the switcher is open;
+    /// returns whether anything on screen changed
+    pub fn handle_event(&mut self, event: &HyprEvent) -> bool {
+        match event.name.as_str() {
+            "activewindowv2" => {
+                let address = events::window_address(&event.data);
+                self.focus_changed(&address);
+                // Urgent only until the window has had focus
+                if self.urgent.remove(&address) {
+                    self.apply_filters();
+                    return true;
+                }
+                false
+            }
+            "urgent" => {
+                self.urgent.insert(events::window_address(&event.data));
+                self.apply_filters();
+                true
+            }
+            // windowtitlev2>>ADDRESS,TITLE — keeps unread counters current
+            "windowtitlev2" => {
+                let fields = event.fields(2);
+                let address = events::window_address(fields[0]);
+                let Some(win) = self.windows.iter_mut().find(|w| w.id == address) else {
+                    return false;
+                };
+                win.title = fields.get(1).unwrap_or(&"").to_string();
+                self.apply_filters();
+                true
+            }
+            // Redrawn once the refresh comes back from the worker
+            "openwindow" | "closewindow" | "movewindowv2" | "createworkspacev2"
+            | "destroyworkspacev2" | "renameworkspace" | "moveworkspacev2" => {
+                self.refresh_windows();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    // Focus moved to the window at `address`: quit unless it is our own terminal
+    fn focus_changed(&mut self, address: &str) {
+        let Some(host) = &self.host else {
+            return;
+        };
+        if !self.auto_close || self.ignore_focus_until.is_some_and(|t| Instant::now() < t) {
+            return;
+        }
+        if *host != address {
+            self.running = false;
+        }
+    }
+
+    /// Turn peek mode on or off. The host terminal is pinned while peeking so
+    /// it stays visible on whichever workspace the peeked window lives on
+    pub fn set_peek(&mut self, on: bool) {
+        let Some(host) = self.host.clone() else {
+            return;
+        };
+        if on == self.peek {
+            return;
+        }
+        self.peek = on;
+        if on && self.host_pinnable && !self.pinned_host {
+            self.submit(Job::dispatch(&["pin", &format!("address:{}", host)]), None, Then::Stay);
+            self.pinned_host = true;
+        }
+        if !on {
+            self.unpin_host();
+            // Back to where we started, keeping the keyboard in the switcher
+            if let (Some(original), Some(_)) = (&self.original, self.peeked.take()) {
+                let batch = format!(
+                    "dispatch focuswindow address:{} ; dispatch focuswindow address:{}",
+                    original, host
+                );
+                self.ignore_focus_until = Some(Instant::now() + FOCUS_GRACE);
+                self.submit(Job::Batch(batch), None, Then::Stay);
+            }
+        }
+    }
+
+    /// Unpin the host again, returning the id of the job doing it so the
+    /// caller can wait for it before exiting
+    pub fn unpin_host(&mut self) -> Option<u64> {
+        if !std::mem::take(&mut self.pinned_host) {
+            return None;
+        }
+        let host = self.host.clone()?;
+        Some(self.submit(Job::dispatch(&["pin", &format!("address:{}", host)]), None, Then::Stay))
+    }
+
+    /// In peek mode, bring the highlighted window forward and hand focus
+    /// straight back to the switcher
+    pub fn update_peek(&mut self) {
+        if !self.peek || !matches!(self.view, View::Grid | View::List | View::Minimap) {
+            return;
+        }
+        let (Some(win), Some(host)) = (self.shown(self.selected_index), &self.host) else {
+            return;
+        };
+        if self.peeked.as_ref() == Some(&win.id) {
+            return;
+        }
+        let batch = format!(
+            "dispatch focuswindow address:{} ; dispatch focuswindow address:{}",
+            win.id, host
+        );
+        self.peeked = Some(win.id.clone());
+        self.ignore_focus_until = Some(Instant::now() + FOCUS_GRACE);
+        self.submit(Job::Batch(batch), None, Then::Stay);
+    }
+
+    // q/Esc: quit without choosing, giving focus back to the window we started
+    // from. Inline, the host is the terminal the user is typing in and already
+    // has focus, so nothing is dispatched.
+    fn cancel(&mut self) {
+        self.chosen = None;
+        let Some(original) = self.original.as_ref().filter(|_| !self.inline) else {
+            self.running = false;
+            return;
+        };
+        self.ignore_focus_until = Some(Instant::now() + FOCUS_GRACE);
+        let job = Job::dispatch(&["focuswindow", &format!("address:{}", original)]);
+        self.submit(job, None, Then::Quit);
+    }
+
+    /// Hand `job` to the worker. `card` names the window whose card shows the
+    /// action while it runs and how it ended; `then` is applied once it ended.
+    /// Returns the id its Done will carry.
+    fn submit(&mut self, job: Job, card: Option<(String, Verb)>, then: Then) -> u64 {
+        let id = self.worker.submit(job, matches!(then, Then::Refresh));
+        self.track(id, card, then);
+        id
+    }
+
+    // Remember what to do once job `id` comes back, see job_done
+    fn track(&mut self, id: u64, card: Option<(String, Verb)>, then: Then) {
+        let window = card.map(|(window, verb)| {
+            self.actions.insert(window.clone(), ActionState::Pending(verb));
+            (window, verb)
+        });
+        self.jobs.insert(id, Action { window, then });
+    }
+
+    fn is_pending(&self, win: &Window) -> bool {
+        matches!(self.actions.get(&win.id), Some(ActionState::Pending(_)))
+    }
+
+    /// A job came back from the worker; returns whether anything on screen changed
+    pub fn job_done(&mut self, done: Done) -> bool {
+        let Some(action) = self.jobs.remove(&done.id) else {
+            return false;
+        };
+        if let Then::Report(index) = action.then {
+            let address = action.window.as_ref().map(|(window, _)| window.clone());
+            self.show_output(index, address.as_deref(), &done);
+        }
+        if let Some((window, verb)) = action.window {
+            let state = match &done.result {
+                Ok(()) => ActionState::Done(verb),
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => ActionState::Failed("timed out".to_string()),
+                Err(e) => ActionState::Failed(e.to_string()),
+            };
+            self.actions.insert(window, state);
+        }
+        match (action.then, done.result.is_ok()) {
+            (Then::Quit, _) | (Then::QuitOnSuccess, true) => self.running = false,
+            _ => {}
+        }
+        if let Some(snapshot) = done.snapshot {
+            self.apply_snapshot(snapshot);
+        }
+        true
+    }
+
+    // Space: pick the selected window for the next custom action, or drop it
+    fn toggle_picked(&mut self) {
+        let Some(win) = self.shown(self.selected_index) else {
+            return;
+        };
+        let id = win.id.clone();
+        if !self.picked.remove(&id) {
+            self.picked.insert(id);
+        }
+    }
+
+    /// Run custom action `index` once for every picked window, or for the
+    /// selected one when nothing is picked
+    fn run_custom_action(&mut self, index: usize) {
+        let Some(template) = self.custom_actions.get(index).map(|a| a.command.clone()) else {
+            return;
+        };
+        let targets: Vec<Window> = if self.picked.is_empty() {
+            self.shown(self.selected_index).cloned().into_iter().collect()
+        } else {
+            self.windows.iter().filter(|w| self.picked.contains(&w.id)).cloned().collect()
+        };
+        for win in targets {
+            let cwd = actions::process_cwd(win.pid).map(|cwd| cwd.to_string_lossy().into_owned());
+            let command = actions::expand(&template, &win, cwd.as_deref());
+            let id = self.worker.run(command);
+            self.track(id, Some((win.id, RUN)), Then::Report(index));
+        }
+    }
+
+    // A custom action finished: its last line as a toast, or everything in the
+    // pager, which collects the output of every window it ran on
+    fn show_output(&mut self, index: usize, address: Option<&str>, done: &Done) {
+        let Some(CustomAction { name, output: mode, .. }) = self.custom_actions.get(index) else {
+            return;
+        };
+        let name = name.clone();
+        let output = done.output.as_deref().unwrap_or_default();
+        match *mode {
+            OutputMode::None => {}
+            OutputMode::Toast => {
+                let last = output.lines().map(str::trim).rfind(|line| !line.is_empty());
+                self.toast = Some(match (&done.result, last) {
+                    (Ok(()), Some(line)) => Ok(format!("{}: {}", name, line)),
+                    (Ok(()), None) => Ok(format!("{}: done", name)),
+                    (Err(e), Some(line)) => Err(format!("{}: {} ({})", name, line, e)),
+                    (Err(e), None) => Err(format!("{}: {}", name, e)),
+                });
+            }
+            OutputMode::Pager => {
+                let window = self.windows.iter().find(|w| Some(w.id.as_str()) == address);
+                let mut lines = vec![match window {
+                    Some(win) => format!("── {} • {}", self.rewriter.class_name(&win.class), win.title),
+                    None => format!("── {}", address.unwrap_or("?")),
+                }];
+                lines.extend(output.lines().map(str::to_string));
+                if let Err(e) = &done.result {
+                    lines.push(format!("[{}]", e));
+                }
+                match &mut self.pager {
+                    Some(pager) if pager.title == name => {
+                        pager.lines.push(String::new());
+                        pager.lines.extend(lines);
+                    }
+                    _ => {
+                        self.pager = Some(Pager {
+                            title: name,
+                            lines,
+                            scroll: 0,
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    /// The first snapshot could not be read: say why over the empty list and
+    /// try again in the background
+    pub fn snapshot_failed(&mut self, error: &io::Error) {
+        self.toast = Some(Err(format!("Could not read the window list: {}", error)));
+        self.refresh_windows();
+    }
+
+    // Re-query the window model in the background, see job_done
+    fn refresh_windows(&mut self) {
+        self.submit(Job::Refresh, None, Then::Refresh);
+    }
+
+    fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        let selected = self.shown(self.selected_index).map(|w| w.id.clone());
+        let old_workspace = self.workspaces.get(self.workspace_index).map(|ws| ws.id);
+        if self.host.is_none() {
+            self.host = popup::find_host(&snapshot.windows);
+        }
+        self.windows = snapshot.windows;
+        self.host_pinnable = pinnable(&self.windows, self.host.as_deref());
+        self.windows.retain(|w| Some(&w.id) != self.host.as_ref());
+        self.workspaces = snapshot.workspaces;
+        self.monitors = snapshot.monitors;
+        self.active_workspace = snapshot.active_workspace;
+        if let Some(i) = self.workspaces.iter().position(|ws| Some(ws.id) == old_workspace) {
+            self.workspace_index = i;
+        }
+        if self.workspace_index >= self.workspaces.len() {
+            self.workspace_index = self.workspaces.len().saturating_sub(1);
+        }
+        if self.monitor_index >= self.monitors.len() {
+            self.monitor_index = self.monitors.len().saturating_sub(1);
+        }
+        self.picked.retain(|id| self.windows.iter().any(|w| &w.id == id));
+        self.apply_filters_keeping(selected);
+        if self.windows.is_empty() {
+            self.running = false;
+        }
+    }
+}
+
+fn handle_prompt_key(app: &mut App, key: KeyEvent) {
+    let Some(prompt) = app.prompt.as_mut() else {
+        return;
+    };
+    match (&prompt.kind, key.code) {
+        (_, KeyCode::Esc) => app.prompt = None,
+        (PromptKind::CloseWorkspaceWindows(_), KeyCode::Char('y')) => app.submit_prompt(),
+        (PromptKind::CloseWorkspaceWindows(_), _) => app.prompt = None,
+        (_, KeyCode::Enter) => app.submit_prompt(),
+        (_, KeyCode::Backspace) => {
+            prompt.input.pop();
+        }
+        (_, KeyCode::Char(c)) => prompt.input.push(c),
+        _ => {}
+    }
+}
+
+// The output pager: scroll with the arrows, Page Up/Down and Home/End
+fn handle_pager_key(app: &mut App, key: KeyEvent) {
+    let Some(pager) = app.pager.as_mut() else {
+        return;
+    };
+    let last = pager.lines.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => pager.scroll = pager.scroll.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => pager.scroll = (pager.scroll + 1).min(last),
+        KeyCode::PageUp => pager.scroll = pager.scroll.saturating_sub(PAGER_PAGE),
+        KeyCode::PageDown | KeyCode::Char(' ') => pager.scroll = (pager.scroll + PAGER_PAGE).min(last),
+        KeyCode::Home | KeyCode::Char('g') => pager.scroll = 0,
+        KeyCode::End | KeyCode::Char('G') => pager.scroll = last,
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => app.pager = None,
+        _ => {}
+    }
+}
+
+fn handle_rule_key(app: &mut App, key: KeyEvent) {
+    let Some(builder) = app.rule_builder.as_mut() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.rule_builder = None,
+        KeyCode::Enter => app.write_rule(),
+        KeyCode::Up | KeyCode::BackTab => builder.previous_field(),
+        KeyCode::Down | KeyCode::Tab => builder.next_field(),
+        KeyCode::Left => builder.cycle(false),
+        KeyCode::Right => builder.cycle(true),
+        KeyCode::Char(c) => builder.edit(Some(c)),
+        KeyCode::Backspace => builder.edit(None),
+        _ => {}
+    }
+}
+
+// Typing into the filter; returns false for keys that should get their normal meaning
+fn handle_filter_key(app: &mut App, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char(c) => {
+            let mut text = app.filter_text.clone();
+            text.push(c);
+            app.set_filter_text(text);
+        }
+        KeyCode::Backspace => {
+            let mut text = app.filter_text.clone();
+            text.pop();
+            app.set_filter_text(text);
+        }
+        KeyCode::Esc => {
+            app.filtering = false;
+            app.set_filter_text(String::new());
+        }
+        KeyCode::Enter => app.activate_selected(),
+        _ => return false,
+    }
+    true
+}
+
+// Inline mode: arrows and Ctrl-N/P move, Enter picks, Esc cancels, and
+// everything else edits the filter
+fn handle_inline_key(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Up | KeyCode::BackTab => app.select_previous(),
+        KeyCode::Down | KeyCode::Tab => app.select_next(),
+        KeyCode::Char('p') if ctrl => app.select_previous(),
+        KeyCode::Char('n') if ctrl => app.select_next(),
+        KeyCode::Char('c') if ctrl => app.cancel(),
+        KeyCode::Esc => app.cancel(),
+        KeyCode::Delete => app.close_selected_window(),
+        _ => {
+            handle_filter_key(app, key);
+        }
+    }
+}
+
+pub fn handle_key(app: &mut App, key: KeyEvent, size: Rect) {
+    // Confirmations have been seen once the user moves on
+    app.actions.retain(|_, state| !matches!(state, ActionState::Done(_)));
+    app.toast = None;
+    if app.inline {
+        handle_inline_key(app, key);
+        return;
+    }
+    if app.prompt.is_some() {
+        handle_prompt_key(app, key);
+        return;
+    }
+    if app.rule_builder.is_some() {
+        handle_rule_key(app, key);
+        return;
+    }
+    if app.pager.is_some() {
+        handle_pager_key(app, key);
+        return;
+    }
+    if app.details {
+        app.details = false;
+        return;
+    }
+    if app.filtering && handle_filter_key(app, key) {
+        return;
+    }
+    if let Some(first) = app.pending_key.take() {
+        if let KeyCode::Char(c) = key.code {
+            if marks::is_mark_key(c) {
+                match first {
+                    'm' => app.set_mark(c),
+                    _ => app.jump_to_mark(c),
+                }
+            }
+        }
+        return;
+    }
+
+    // Custom actions come first, so the config can take over any key
+    if matches!(app.view, View::Grid | View::List | View::Minimap) {
+        if let Some(i) = app.custom_actions.iter().position(|a| actions::key_matches(&a.key, key)) {
+            app.run_custom_action(i);
+            return;
+        }
+    }
+
+    let (_, _, grid) = split_screen(size);
+    match (app.view, key.code) {
+        (View::Minimap, KeyCode::Left) => app.select_direction(Direction::Left),
+        (View::Minimap, KeyCode::Right) => app.select_direction(Direction::Right),
+        (View::Minimap, KeyCode::Up) => app.select_direction(Direction::Up),
+        (View::Minimap, KeyCode::Down) => app.select_direction(Direction::Down),
+        (View::List, KeyCode::Up) => app.select_previous(),
+        (View::List, KeyCode::Down) => app.select_next(),
+        (View::List, KeyCode::Left) | (View::List, KeyCode::Right) => {}
+        (View::List, KeyCode::Char('s')) => app.cycle_sort(),
+        (_, KeyCode::Left) => app.select_in_grid(grid, GridLayout::left),
+        (_, KeyCode::Right) => app.select_in_grid(grid, GridLayout::right),
+        (_, KeyCode::Up) => app.select_in_grid(grid, GridLayout::up),
+        (_, KeyCode::Down) => app.select_in_grid(grid, GridLayout::down),
+        (_, KeyCode::Tab) => app.toggle_view(),
+        (_, KeyCode::Char('v')) => app.toggle_list(),
+        (View::Workspaces, KeyCode::Enter) => app.switch_to_selected_workspace(),
+        (View::Monitors, KeyCode::Enter) => app.focus_selected_monitor(),
+        (View::Monitors, KeyCode::Delete) | (View::Monitors, KeyCode::Char('x')) => {}
+        (View::Workspaces, KeyCode::Char('e')) => {
+            app.open_prompt(PromptKind::RenameWorkspace(app.workspace_index))
+        }
+        (View::Workspaces, KeyCode::Char('n')) => app.open_prompt(PromptKind::CreateWorkspace),
+        (View::Workspaces, KeyCode::Char('M')) => app.move_selected_workspace_to_next_monitor(),
+        (View::Workspaces, KeyCode::Delete) | (View::Workspaces, KeyCode::Char('x')) => {
+            app.open_prompt(PromptKind::CloseWorkspaceWindows(app.workspace_index))
+        }
+        (View::Grid, KeyCode::Char('/')) | (View::List, KeyCode::Char('/')) => app.filtering = true,
+        (View::Grid, KeyCode::Char(c @ ('m' | '\'')))
+        | (View::List, KeyCode::Char(c @ ('m' | '\'')))
+        | (View::Minimap, KeyCode::Char(c @ ('m' | '\''))) => app.pending_key = Some(c),
+        (View::Grid, KeyCode::Esc) | (View::List, KeyCode::Esc) if !app.filter_text.is_empty() => {
+            app.set_filter_text(String::new())
+        }
+        (_, KeyCode::Char('o')) => app.toggle_monitor_filter(),
+        (_, KeyCode::Char('w')) => app.toggle_workspace_filter(),
+        (_, KeyCode::Enter) => app.activate_selected(),
+        (_, KeyCode::Delete) | (_, KeyCode::Char('x')) => app.close_selected_window(),
+        (_, KeyCode::Char('r')) => app.refresh_windows(),
+        (_, KeyCode::Char('p')) => app.set_peek(!app.peek),
+        (View::Grid, KeyCode::Char('i')) | (View::List, KeyCode::Char('i')) | (View::Minimap, KeyCode::Char('i')) => {
+            app.details = app.shown(app.selected_index).is_some()
+        }
+        (View::Grid, KeyCode::Char('u')) | (View::List, KeyCode::Char('u')) | (View::Minimap, KeyCode::Char('u')) => {
+            app.select_next_urgent()
+        }
+        (View::Grid, KeyCode::Char('t')) | (View::List, KeyCode::Char('t')) | (View::Minimap, KeyCode::Char('t')) => {
+            app.open_tags_prompt()
+        }
+        (View::Grid, KeyCode::Char('R')) | (View::List, KeyCode::Char('R')) | (View::Minimap, KeyCode::Char('R')) => {
+            app.open_rule_builder()
+        }
+        (View::Grid, KeyCode::Char(' ')) | (View::List, KeyCode::Char(' ')) | (View::Minimap, KeyCode::Char(' ')) => {
+            app.toggle_picked()
+        }
+        (_, KeyCode::Char('q')) | (_, KeyCode::Esc) => app.cancel(),
+        _ => {}
+    }
+}
+
+// Mouse handling for the workspace and monitor cards: select, and activate on double-click
+fn handle_overview_mouse(app: &mut App, me: MouseEvent, area: Rect) {
+    let hit = hit_test(app, me.column, me.row, area);
+    match me.kind {
+        MouseEventKind::Moved if app.hover_select => {
+            if let Some(idx) = hit {
+                *app.selection_mut() = idx;
+            }
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            let Some(idx) = hit else {
+                app.last_click = None;
+                return;
+            };
+            *app.selection_mut() = idx;
+            let now = Instant::now();
+            let is_double_click = matches!(
+                app.last_click,
+                Some((last_idx, at)) if last_idx == idx && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL
+            );
+            if is_double_click && app.view == View::Monitors {
+                app.focus_selected_monitor();
+            } else if is_double_click {
+                app.switch_to_selected_workspace();
+            } else {
+                app.last_click = Some((idx, now));
+            }
+        }
+        MouseEventKind::ScrollDown => app.select_next(),
+        MouseEventKind::ScrollUp => app.select_previous(),
+        _ => {}
+    }
+}
+
+pub fn handle_mouse(app: &mut App, me: MouseEvent, size: Rect) {
+    let (_, _, area) = split_screen(size);
+    if app.prompt.is_some() || app.rule_builder.is_some() || app.pager.is_some() {
+        return;
+    }
+    if matches!(app.view, View::Workspaces | View::Monitors) {
+        handle_overview_mouse(app, me, area);
+        return;
+    }
+    if let (View::List, MouseEventKind::Down(MouseButton::Left)) = (app.view, me.kind) {
+        if let Some(column) = ListLayout::new(app, area).column_at(me.column, me.row) {
+            app.sort_by(column);
+            return;
+        }
+    }
+    match me.kind {
+        MouseEventKind::Moved if app.hover_select => {
+            if let Some(idx) = window_at(app, me.column, me.row, area) {
+                app.selected_index = idx;
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            let over = hit_target(app, me.column, me.row, size);
+            if let Some(drag) = app.drag.as_mut() {
+                drag.moved = true;
+                drag.over = over;
+                app.last_click = None;
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            let Some(drag) = app.drag.take() else {
+                return;
+            };
+            if !drag.moved {
+                return;
+            }
+            match hit_target(app, me.column, me.row, size) {
+                Some(HitTarget::Workspace(ws)) => app.move_window_to_workspace(drag.source, ws),
+                Some(HitTarget::Card(target)) => app.swap_windows(drag.source, target),
+                None => {}
+            }
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            // Always act on the card under the pointer, not the last hovered one
+            let Some(idx) = window_at(app, me.column, me.row, area) else {
+                app.last_click = None;
+                return;
+            };
+            app.selected_index = idx;
+            app.drag = Some(Drag {
+                source: idx,
+                over: None,
+                moved: false,
+            });
+
+            let now = Instant::now();
+            let is_double_click = matches!(
+                app.last_click,
+                Some((last_idx, at)) if last_idx == idx && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL
+            );
+            if is_double_click {
+                app.last_click = None;
+                app.drag = None;
+                app.activate_selected();
+            } else {
+                app.last_click = Some((idx, now));
+            }
+        }
+        MouseEventKind::Down(MouseButton::Middle) | MouseEventKind::Down(MouseButton::Right) => {
+            // Middle- or right-click closes the window under the pointer
+            if let Some(idx) = window_at(app, me.column, me.row, area) {
+                app.selected_index = idx;
+                app.close_selected_window();
+            }
+        }
+        MouseEventKind::ScrollDown => app.select_next(),
+        MouseEventKind::ScrollUp => app.select_previous(),
+        _ => {}
+    }
+}
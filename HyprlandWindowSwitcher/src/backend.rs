@@ -0,0 +1,221 @@
+// hyprctl access: queries for the window model and dispatching commands
+
+use std::io::{self, Read};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::model::{Monitor, Window, Workspace};
+
+/// Longest a hyprctl call may take; a compositor that does not answer by
+/// then gets the call killed and reported as timed out
+pub const HYPRCTL_TIMEOUT: Duration = Duration::from_secs(2);
+
+// How often a running hyprctl is checked for having exited
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Run hyprctl with `args` and return its output, killing it after HYPRCTL_TIMEOUT
+pub fn hyprctl(args: &[&str]) -> io::Result<Vec<u8>> {
+    let mut command = Command::new("hyprctl");
+    command.args(args);
+    run_with_timeout(command, &format!("hyprctl {}", args.join(" ")), HYPRCTL_TIMEOUT)
+}
+
+// Run `command` and return its stdout, killing it once `timeout` passed.
+// Stdout is drained on a thread of its own while waiting: a reply larger than
+// the pipe buffer (`clients -j` with many windows) would otherwise block the
+// child on a full pipe until the timeout.
+fn run_with_timeout(mut command: Command, what: &str, timeout: Duration) -> io::Result<Vec<u8>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+    let deadline = Instant::now() + timeout;
+    while child.try_wait()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::TimedOut, format!("{} timed out", what)));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    reader
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other(format!("reading the output of {} failed", what))))
+}
+
+// hyprctl answers "ok" for every dispatch it ran, anything else is an error
+fn check_reply(output: &[u8]) -> io::Result<()> {
+    let reply = String::from_utf8_lossy(output);
+    match reply.lines().map(str::trim).find(|line| !line.is_empty() && *line != "ok") {
+        Some(error) => Err(io::Error::other(error.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Run a dispatcher and check that Hyprland accepted it
+pub fn try_dispatch(args: &[String]) -> io::Result<()> {
+    let mut command = vec!["dispatch"];
+    command.extend(args.iter().map(String::as_str));
+    check_reply(&hyprctl(&command)?)
+}
+
+/// Run `;`-separated `dispatch ...` commands in one call
+pub fn try_batch(batch: &str) -> io::Result<()> {
+    check_reply(&hyprctl(&["--batch", batch])?)
+}
+
+/// Reload the Hyprland config
+pub fn try_reload() -> io::Result<()> {
+    check_reply(&hyprctl(&["reload"])?)
+}
+
+/// Clients as hyprctl reports them, or why they could not be read
+pub fn get_windows() -> io::Result<Vec<Window>> {
+    let output = hyprctl(&["clients", "-j"])?;
+    Ok(json_list(&output, "clients")?
+        .iter()
+        .map(|c| Window {
+            id: c["address"].as_str().unwrap_or("").to_string(),
+            class: c["class"].as_str().unwrap_or("UnknownClass").to_string(),
+            title: c["title"].as_str().unwrap_or("No Title").to_string(),
+            workspace: c["workspace"]["id"]
+                .as_i64()
+                .map(|id| id.to_string())
+                .unwrap_or("?".to_string()),
+            workspace_id: c["workspace"]["id"].as_i64().unwrap_or(0),
+            monitor: c["monitor"].as_i64().unwrap_or(-1),
+            at: (c["at"][0].as_i64().unwrap_or(0), c["at"][1].as_i64().unwrap_or(0)),
+            size: (c["size"][0].as_i64().unwrap_or(0), c["size"][1].as_i64().unwrap_or(0)),
+            floating: c["floating"].as_bool().unwrap_or(false),
+            pid: c["pid"].as_i64().unwrap_or(-1),
+            initial_class: c["initialClass"].as_str().unwrap_or("").to_string(),
+            focus_history: c["focusHistoryID"].as_i64().unwrap_or(i64::MAX),
+            pinned: c["pinned"].as_bool().unwrap_or(false),
+            // A bool on older Hyprland versions, a fullscreen mode number on newer ones
+            fullscreen: c["fullscreen"]
+                .as_bool()
+                .or_else(|| c["fullscreen"].as_i64().map(|mode| mode != 0))
+                .unwrap_or(false),
+            hidden: c["hidden"].as_bool().unwrap_or(false),
+            // Tags set at runtime carry a trailing `*` that is not part of the name
+            tags: c["tags"]
+                .as_array()
+                .map(|tags| {
+                    tags.iter()
+                        .filter_map(Value::as_str)
+                        .map(|tag| tag.trim_end_matches('*').to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect())
+}
+
+pub fn get_monitors() -> io::Result<Vec<Monitor>> {
+    parse_monitors(&hyprctl(&["monitors", "-j"])?)
+}
+
+// A JSON list from hyprctl; a truncated or garbled reply is an error rather
+// than an empty list
+fn json_list(output: &[u8], what: &str) -> io::Result<Vec<Value>> {
+    match serde_json::from_slice(output)? {
+        Value::Array(list) => Ok(list),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("hyprctl {} is not a list", what))),
+    }
+}
+
+fn parse_monitors(output: &[u8]) -> io::Result<Vec<Monitor>> {
+    Ok(json_list(output, "monitors")?
+        .iter()
+        .map(|m| Monitor {
+            id: m["id"].as_i64().unwrap_or(-1),
+            name: m["name"].as_str().unwrap_or("?").to_string(),
+            x: m["x"].as_i64().unwrap_or(0),
+            y: m["y"].as_i64().unwrap_or(0),
+            width: m["width"].as_i64().unwrap_or(0),
+            height: m["height"].as_i64().unwrap_or(0),
+            scale: m["scale"].as_f64().unwrap_or(1.0),
+            refresh_rate: m["refreshRate"].as_f64().unwrap_or(0.0),
+            focused: m["focused"].as_bool().unwrap_or(false),
+            active_workspace_id: m["activeWorkspace"]["id"].as_i64().unwrap_or(0),
+            active_workspace: m["activeWorkspace"]["name"]
+                .as_str()
+                .unwrap_or("?")
+                .to_string(),
+        })
+        .collect())
+}
+
+pub fn get_workspaces() -> io::Result<Vec<Workspace>> {
+    parse_workspaces(&hyprctl(&["workspaces", "-j"])?)
+}
+
+fn parse_workspaces(output: &[u8]) -> io::Result<Vec<Workspace>> {
+    let mut workspaces: Vec<Workspace> = json_list(output, "workspaces")?
+        .iter()
+        .map(|w| Workspace {
+            id: w["id"].as_i64().unwrap_or(0),
+            name: w["name"].as_str().unwrap_or("?").to_string(),
+            monitor: w["monitor"].as_str().unwrap_or("?").to_string(),
+            windows: w["windows"].as_u64().unwrap_or(0) as usize,
+        })
+        .collect();
+
+    // Group by monitor, then order workspaces the way Hyprland numbers them
+    workspaces.sort_by(|a, b| a.monitor.cmp(&b.monitor).then(a.id.cmp(&b.id)));
+    Ok(workspaces)
+}
+
+pub fn get_active_workspace_id() -> Option<i64> {
+    let output = hyprctl(&["activeworkspace", "-j"]).ok()?;
+    let data: Value = serde_json::from_slice(&output).ok()?;
+    data["id"].as_i64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[test]
+    fn replies_larger_than_the_pipe_buffer_are_read_in_full() {
+        // About what `clients -j` prints for a hundred windows
+        let big = shell("head -c 200000 /dev/zero | tr '\\0' x");
+        let output = run_with_timeout(big, "big", HYPRCTL_TIMEOUT).unwrap();
+        assert_eq!(output.len(), 200_000);
+        assert!(output.iter().all(|&b| b == b'x'));
+    }
+
+    #[test]
+    fn garbled_replies_are_errors_not_empty_lists() {
+        let truncated = br#"[{"id": 1, "name": "1", "monitor": "DP-1", "windows": 2}, {"id""#;
+        assert!(parse_workspaces(truncated).is_err());
+        assert_eq!(parse_monitors(b"unknown request").unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(parse_monitors(br#"{"id": 0}"#).unwrap_err().to_string(), "hyprctl monitors is not a list");
+        let workspaces = parse_workspaces(br#"[{"id": 2, "monitor": "B"}, {"id": 1, "monitor": "A"}]"#).unwrap();
+        assert_eq!(workspaces.iter().map(|w| w.id).collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn slow_commands_are_killed() {
+        let started = Instant::now();
+        let error = run_with_timeout(shell("sleep 5"), "slow", Duration::from_millis(100)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(error.to_string(), "slow timed out");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
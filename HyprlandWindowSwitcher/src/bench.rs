@@ -0,0 +1,194 @@
+// Startup and rendering benchmarks
+//
+// `bench` times what the switcher does between process start and its first
+// frame (config, window model, first render into an off-screen buffer), once
+// querying hyprctl directly and once reading from the daemon when it runs.
+// `bench render` draws frames and hit-tests a grid of synthetic windows, so
+// it needs no compositor.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use ratatui::backend::TestBackend;
+use ratatui::layout::Rect;
+use ratatui::Terminal;
+
+use crate::config::Config;
+use crate::daemon::{self, Snapshot};
+use crate::history::History;
+use crate::app::{App, Options};
+use crate::layout::hit_target;
+use crate::marks::Marks;
+use crate::model::{Monitor, Window, Workspace};
+use crate::ui::draw;
+
+const DEFAULT_RUNS: usize = 20;
+const DEFAULT_WINDOWS: usize = 500;
+const BENCH_WORKSPACES: i64 = 10;
+
+// Off-screen terminal size, roughly a floating terminal on a 1440p monitor
+const BENCH_WIDTH: u16 = 160;
+const BENCH_HEIGHT: u16 = 48;
+
+fn first_frame(source: fn() -> io::Result<Snapshot>) -> io::Result<Duration> {
+    let start = Instant::now();
+    let config = Config::load();
+    let options = Options::from_args(std::iter::empty());
+    let app = App::new(&options, &config, source()?);
+    let mut terminal = Terminal::new(TestBackend::new(BENCH_WIDTH, BENCH_HEIGHT))?;
+    terminal.draw(|f| draw(f, &app))?;
+    Ok(start.elapsed())
+}
+
+fn print_timings(label: &str, mut timings: Vec<Duration>) {
+    timings.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    println!(
+        "  {:<8}  min {:>7.2} ms  median {:>7.2} ms  max {:>7.2} ms",
+        label,
+        ms(timings[0]),
+        ms(timings[timings.len() / 2]),
+        ms(timings[timings.len() - 1])
+    );
+}
+
+// Windows spread over a few workspaces on two monitors, with varied titles
+fn synthetic_snapshot(count: usize) -> Snapshot {
+    const CLASSES: [&str; 6] = ["firefox", "kitty", "code", "slack", "spotify", "obsidian"];
+    let windows = (0..count)
+        .map(|i| {
+            let workspace_id = i as i64 % BENCH_WORKSPACES + 1;
+            Window {
+                monitor: workspace_id % 2,
+                at: ((i as i64 % 4) * 480, (i as i64 % 3) * 360),
+                size: (480, 360),
+                floating: i % 7 == 0,
+                pid: 1000 + i as i64,
+                focus_history: i as i64,
+                ..Window::new(
+                    &format!("0x{:x}", 0x1000 + i),
+                    CLASSES[i % CLASSES.len()],
+                    &format!("Document {} — some longer window title that has to wrap", i),
+                    workspace_id,
+                )
+            }
+        })
+        .collect();
+    let workspaces = (1..=BENCH_WORKSPACES)
+        .map(|id| Workspace {
+            id,
+            name: id.to_string(),
+            monitor: format!("DP-{}", id % 2 + 1),
+            windows: count / BENCH_WORKSPACES as usize,
+        })
+        .collect();
+    let monitors = (0..2)
+        .map(|id| Monitor {
+            x: id * 1920,
+            focused: id == 0,
+            active_workspace_id: id + 1,
+            active_workspace: (id + 1).to_string(),
+            ..Monitor::new(id, &format!("DP-{}", id + 1), 1920, 1080)
+        })
+        .collect();
+    Snapshot {
+        windows,
+        workspaces,
+        monitors,
+        active_workspace: Some(1),
+        urgent: Vec::new(),
+    }
+}
+
+fn time_runs(runs: usize, mut f: impl FnMut() -> io::Result<()>) -> io::Result<Vec<Duration>> {
+    (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f()?;
+            Ok(start.elapsed())
+        })
+        .collect()
+}
+
+fn bench_render(runs: usize, windows: usize) -> io::Result<()> {
+    let config = Config::load();
+    let options = Options::from_args(std::iter::empty());
+    // No host lookup and no saved marks or history, so only drawing is timed
+    let app = App::with_state(
+        &options,
+        &config,
+        synthetic_snapshot(windows),
+        None,
+        Marks::default(),
+        History::default(),
+    );
+    let mut terminal = Terminal::new(TestBackend::new(BENCH_WIDTH, BENCH_HEIGHT))?;
+    let size = Rect::new(0, 0, BENCH_WIDTH, BENCH_HEIGHT);
+
+    println!("Rendering {} synthetic windows over {} runs", windows, runs);
+    let cold = time_runs(runs, || {
+        app.grid_layout.replace(None);
+        terminal.draw(|f| draw(f, &app)).map(|_| ())
+    })?;
+    print_timings("frame", cold);
+    let warm = time_runs(runs, || terminal.draw(|f| draw(f, &app)).map(|_| ()))?;
+    print_timings("cached", warm);
+    // One hit test per cell, as a mouse sweep over the whole screen would do
+    let hits = time_runs(runs, || {
+        for y in 0..BENCH_HEIGHT {
+            for x in 0..BENCH_WIDTH {
+                std::hint::black_box(hit_target(&app, x, y, size));
+            }
+        }
+        Ok(())
+    })?;
+    print_timings("hit test", hits);
+    Ok(())
+}
+
+fn positive(value: Option<String>, flag: &str) -> io::Result<usize> {
+    value
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} expects a positive number", flag),
+            )
+        })
+}
+
+/// `bench [render] [--runs N] [--windows N]`: time to first frame with and
+/// without the daemon, or frame and hit-test times for a large synthetic grid
+pub fn run(mut args: impl Iterator<Item = String>) -> io::Result<()> {
+    let mut runs = DEFAULT_RUNS;
+    let mut windows = DEFAULT_WINDOWS;
+    let mut render = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "render" => render = true,
+            "--runs" => runs = positive(args.next(), "--runs")?,
+            "--windows" => windows = positive(args.next(), "--windows")?,
+            _ => eprintln!("ignoring unknown argument: {}", arg),
+        }
+    }
+    if render {
+        return bench_render(runs, windows);
+    }
+
+    println!("Time to first frame over {} runs", runs);
+    let direct = (0..runs)
+        .map(|_| first_frame(Snapshot::try_query))
+        .collect::<io::Result<Vec<_>>>()?;
+    print_timings("direct", direct);
+
+    if daemon::fetch().is_err() {
+        println!("  daemon    not running, start it with `hypr_window_switcher daemon`");
+        return Ok(());
+    }
+    let attached = (0..runs)
+        .map(|_| first_frame(daemon::fetch))
+        .collect::<io::Result<Vec<_>>>()?;
+    print_timings("daemon", attached);
+    Ok(())
+}
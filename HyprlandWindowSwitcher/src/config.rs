@@ -0,0 +1,142 @@
+// User configuration, read from $XDG_CONFIG_HOME/hypr_window_switcher/config.toml
+//
+// Every section is optional; a missing or unreadable file gives the defaults.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Pinned apps shown as ghost cards while they are not running
+    pub favourites: Vec<Favourite>,
+    pub stats: StatsConfig,
+    // Display names for window classes, e.g. "com.mitchellh.ghostty" = "Ghostty"
+    pub aliases: BTreeMap<String, String>,
+    // Applied in order to card titles
+    pub rewrites: Vec<TitleRewrite>,
+    pub rules: RulesConfig,
+    // Shell commands bound to keys, run on the selected or picked windows
+    pub actions: Vec<CustomAction>,
+    // Why the file or part of it was ignored; the switcher shows these once
+    // its screen is up, where anything printed would be drawn over
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomAction {
+    // A character, optionally after "ctrl+", e.g. "S" or "ctrl+d"
+    pub key: String,
+    pub name: String,
+    // Run with `sh -c`; placeholders like {title} become shell-quoted values
+    pub command: String,
+    #[serde(default)]
+    pub output: OutputMode,
+}
+
+// Where the output of a custom action goes once it finished
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    // Last line in a one-line message, until the next key
+    #[default]
+    Toast,
+    // All of it in a scrollable popup
+    Pager,
+    // Only the card badge
+    None,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RulesConfig {
+    // Config files the rule builder can append to, the first one preselected;
+    // `~/` is expanded. Defaults to hypr/hyprland.conf
+    pub files: Vec<String>,
+    // Preselect reloading Hyprland once a rule was written
+    pub reload: bool,
+}
+
+impl RulesConfig {
+    pub fn files(&self) -> Vec<PathBuf> {
+        if self.files.is_empty() {
+            return vec![config_home().join("hypr").join("hyprland.conf")];
+        }
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap_or_default());
+        self.files
+            .iter()
+            .map(|file| match file.strip_prefix("~/") {
+                Some(rest) => home.join(rest),
+                None => PathBuf::from(file),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    // Named title regexes for `stats --by pattern`, first match wins
+    pub patterns: Vec<TitlePattern>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TitlePattern {
+    pub name: String,
+    pub regex: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TitleRewrite {
+    // Regex the window class has to match, every class when omitted
+    pub class: Option<String>,
+    pub pattern: String,
+    // Replacement text, may refer to capture groups as $1 or ${name}
+    #[serde(default)]
+    pub replace: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Favourite {
+    // Window class used to tell whether the app is already running
+    pub class: String,
+    // Shell command passed to `hyprctl dispatch exec`
+    pub command: String,
+    // Label for the card, defaults to the class
+    pub name: Option<String>,
+}
+
+// $XDG_CONFIG_HOME, or ~/.config
+fn config_home() -> PathBuf {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| {
+            let home = std::env::var_os("HOME").unwrap_or_default();
+            PathBuf::from(home).join(".config")
+        })
+}
+
+pub fn config_path() -> PathBuf {
+    config_home().join("hypr_window_switcher").join("config.toml")
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let path = config_path();
+        let Ok(raw) = fs::read_to_string(&path) else {
+            return Config::default();
+        };
+        match toml::from_str(&raw) {
+            Ok(config) => config,
+            Err(e) => Config {
+                warnings: vec![format!("ignoring invalid config {}: {}", path.display(), e)],
+                ..Config::default()
+            },
+        }
+    }
+}
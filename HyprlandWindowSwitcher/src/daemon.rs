@@ -0,0 +1,154 @@
+// Resident daemon keeping the window model warm
+//
+// `daemon` queries Hyprland once, then re-queries whenever the event socket
+// reports a change and hands the latest snapshot to every client connecting to
+// its Unix socket. The switcher reads that snapshot instead of running four
+// hyprctl commands before its first frame, and queries directly when no
+// daemon is listening.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{get_active_workspace_id, get_monitors, get_windows, get_workspaces};
+use crate::events;
+use crate::model::{Monitor, Window, Workspace};
+
+// Events arrive in bursts (a window opening also moves focus and workspaces),
+// wait this long after the first one so a burst costs a single refresh
+const DEBOUNCE: Duration = Duration::from_millis(15);
+
+// A daemon that does not answer within this time is treated as absent
+const CLIENT_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub windows: Vec<Window>,
+    pub workspaces: Vec<Workspace>,
+    pub monitors: Vec<Monitor>,
+    pub active_workspace: Option<i64>,
+    // Windows that raised an `urgent` event and have not had focus since;
+    // only the daemon sees those events, a direct query leaves this empty
+    #[serde(default)]
+    pub urgent: Vec<String>,
+}
+
+impl Snapshot {
+    /// Ask Hyprland directly, failing when hyprctl cannot be run or does
+    /// not answer in time
+    pub fn try_query() -> io::Result<Self> {
+        Ok(Snapshot {
+            windows: get_windows()?,
+            workspaces: get_workspaces()?,
+            monitors: get_monitors()?,
+            active_workspace: get_active_workspace_id(),
+            urgent: Vec::new(),
+        })
+    }
+
+    /// The daemon's snapshot when one is running, else a direct query
+    pub fn load() -> io::Result<Self> {
+        fetch().or_else(|_| Snapshot::try_query())
+    }
+}
+
+pub fn socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("hypr_window_switcher.sock")
+}
+
+/// Read the current snapshot from a running daemon
+pub fn fetch() -> io::Result<Snapshot> {
+    let mut stream = UnixStream::connect(socket_path())?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw)?;
+    serde_json::from_slice(&raw).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// `daemon`: serve snapshots until Hyprland closes the event socket
+pub fn serve() -> io::Result<()> {
+    let path = socket_path();
+    if UnixStream::connect(&path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("a daemon is already listening on {}", path.display()),
+        ));
+    }
+    let events = events::connect()?;
+    let snapshot = Arc::new(Mutex::new(Snapshot::try_query()?));
+    // Left behind by a daemon that did not shut down cleanly
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    eprintln!("Serving window snapshots on {}", path.display());
+
+    let (changed, pending) = mpsc::channel::<()>();
+
+    let latest = Arc::clone(&snapshot);
+    thread::spawn(move || {
+        while pending.recv().is_ok() {
+            thread::sleep(DEBOUNCE);
+            while pending.try_recv().is_ok() {}
+            // A failed refresh keeps serving the last good snapshot; the next
+            // event tries again
+            let mut fresh = match Snapshot::try_query() {
+                Ok(fresh) => fresh,
+                Err(e) => {
+                    eprintln!("Keeping the previous snapshot, refreshing failed: {}", e);
+                    continue;
+                }
+            };
+            let mut snapshot = latest.lock().unwrap();
+            fresh.urgent = std::mem::take(&mut snapshot.urgent);
+            fresh.urgent.retain(|address| fresh.windows.iter().any(|w| &w.id == address));
+            *snapshot = fresh;
+        }
+    });
+
+    let served = Arc::clone(&snapshot);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let json = serde_json::to_vec(&*served.lock().unwrap());
+            if let Ok(json) = json {
+                let _ = stream.write_all(&json);
+            }
+        }
+    });
+
+    let mut result = Ok(());
+    for event in events {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        match event.name.as_str() {
+            "urgent" | "activewindowv2" => {
+                let address = events::window_address(&event.data);
+                let urgent = &mut snapshot.lock().unwrap().urgent;
+                urgent.retain(|a| *a != address);
+                if event.name == "urgent" {
+                    urgent.push(address);
+                }
+            }
+            _ => {}
+        }
+        let _ = changed.send(());
+    }
+    let _ = fs::remove_file(&path);
+    result
+}
@@ -0,0 +1,92 @@
+// Hyprland event socket (.socket2.sock): one `name>>data` line per event
+
+use std::io::{self, BufRead, BufReader, Lines};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+#[derive(Debug, Clone)]
+pub struct HyprEvent {
+    pub name: String,
+    pub data: String,
+}
+
+impl HyprEvent {
+    fn parse(line: &str) -> Option<Self> {
+        let (name, data) = line.split_once(">>")?;
+        Some(HyprEvent {
+            name: name.to_string(),
+            data: data.to_string(),
+        })
+    }
+
+    /// Split the payload into at most `n` comma-separated fields; the last
+    /// field keeps any further commas (titles may contain them)
+    pub fn fields(&self, n: usize) -> Vec<&str> {
+        self.data.splitn(n, ',').collect()
+    }
+}
+
+/// Events carry window addresses without the 0x prefix hyprctl uses
+pub fn window_address(raw: &str) -> String {
+    format!("0x{}", raw.trim_start_matches("0x"))
+}
+
+pub fn socket_path() -> io::Result<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "HYPRLAND_INSTANCE_SIGNATURE is not set, is Hyprland running?",
+        )
+    })?;
+
+    // Hyprland >= 0.40 keeps its sockets under XDG_RUNTIME_DIR, older versions in /tmp
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("hypr").join(&signature).join(".socket2.sock"));
+    let legacy = PathBuf::from("/tmp/hypr").join(&signature).join(".socket2.sock");
+    Ok(runtime.filter(|p| p.exists()).unwrap_or(legacy))
+}
+
+pub struct Events {
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+pub fn connect() -> io::Result<Events> {
+    let stream = UnixStream::connect(socket_path()?)?;
+    Ok(Events {
+        lines: BufReader::new(stream).lines(),
+    })
+}
+
+impl Iterator for Events {
+    type Item = io::Result<HyprEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.lines.next()? {
+                Ok(line) => {
+                    if let Some(event) = HyprEvent::parse(&line) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Follow the event socket from a background thread, sending every event
+/// on; nothing is sent when Hyprland's socket cannot be reached
+pub fn watch<T: From<HyprEvent> + Send + 'static>(sender: Sender<T>) {
+    thread::spawn(move || {
+        let Ok(events) = connect() else {
+            return;
+        };
+        for event in events.map_while(Result::ok) {
+            if sender.send(event.into()).is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,208 @@
+// Card grid geometry
+//
+// Rendering, mouse hit testing and arrow-key navigation all work from the same
+// GridLayout, so a card is always selected, clicked and drawn in one place.
+
+use ratatui::layout::{Constraint, Layout, Rect};
+
+/// Height of a card in rows: icon line, two heading lines, two body lines, footer, borders
+const CARD_HEIGHT: u16 = 10;
+
+// Narrowest card that still shows a readable title
+const MIN_CELL_WIDTH: usize = 25;
+
+/// Columns for `item_count` cards on a terminal `terminal_width` cells wide,
+/// with the card width and the text width inside a card
+fn calculate_optimal_layout(item_count: usize, terminal_width: u16) -> (usize, usize, usize) {
+    let max_cols = (terminal_width as usize / MIN_CELL_WIDTH).max(1);
+
+    let optimal_cols = if item_count <= 3 {
+        item_count.max(1)
+    } else if terminal_width < 80 {
+        2
+    } else if terminal_width < 120 {
+        3
+    } else {
+        4
+    }
+    .min(max_cols);
+
+    let cell_width = (terminal_width as usize / optimal_cols).saturating_sub(4); // Account for borders and padding
+    let text_width = cell_width.saturating_sub(4); // Account for padding within cell
+
+    (optimal_cols, cell_width, text_width)
+}
+
+/// Card positions for `count` cards filling rows of `cols` from the top left
+pub struct GridLayout {
+    pub count: usize,
+    pub area: Rect,
+    pub cols: usize,
+    pub text_width: usize,
+    /// One rect per card; cards below the area get a zero height
+    pub rects: Vec<Rect>,
+}
+
+impl GridLayout {
+    pub fn new(count: usize, area: Rect) -> Self {
+        let (cols, _, text_width) = calculate_optimal_layout(count, area.width);
+        let col_rects = Layout::horizontal(vec![Constraint::Ratio(1, cols as u32); cols]).split(area);
+        // Rows all have the same height, so they are placed directly: the
+        // layout solver slows down badly with one constraint per row of a
+        // few hundred windows
+        let rects = (0..count)
+            .map(|i| {
+                let col = col_rects[i % cols];
+                let top = (i / cols).saturating_mul(CARD_HEIGHT as usize);
+                let top = top.min(area.height as usize) as u16;
+                let height = CARD_HEIGHT.min(area.height - top);
+                Rect::new(col.x, area.y + top, col.width, height)
+            })
+            .collect();
+
+        GridLayout {
+            count,
+            area,
+            cols,
+            text_width,
+            rects,
+        }
+    }
+
+    /// Card drawn at a terminal cell
+    pub fn card_at(&self, x: u16, y: u16) -> Option<usize> {
+        if !self.area.contains((x, y).into()) {
+            return None;
+        }
+        let col = self.rects.iter().take(self.cols).position(|r| x >= r.x && x < r.x + r.width)?;
+        let row = ((y - self.area.y) / CARD_HEIGHT) as usize;
+        let index = row * self.cols + col;
+        self.rects.get(index).filter(|r| r.height > 0).map(|_| index)
+    }
+
+    /// Previous card, continuing at the end of the row above
+    pub fn left(&self, selected: usize) -> usize {
+        selected.saturating_sub(1)
+    }
+
+    /// Next card, continuing at the start of the row below
+    pub fn right(&self, selected: usize) -> usize {
+        (selected + 1).min(self.count.saturating_sub(1))
+    }
+
+    pub fn up(&self, selected: usize) -> usize {
+        selected.checked_sub(self.cols).unwrap_or(selected)
+    }
+
+    /// Card below; the last card when the row below is too short to have one
+    /// in this column
+    pub fn down(&self, selected: usize) -> usize {
+        let last = self.count.saturating_sub(1);
+        if selected / self.cols < last / self.cols {
+            (selected + self.cols).min(last)
+        } else {
+            selected
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEIGHT: u16 = 40;
+
+    fn grid(count: usize, width: u16) -> GridLayout {
+        GridLayout::new(count, Rect::new(0, 7, width, HEIGHT))
+    }
+
+    #[test]
+    fn columns_follow_the_width_breakpoints() {
+        assert_eq!(grid(10, 40).cols, 1);
+        assert_eq!(grid(10, 79).cols, 2);
+        assert_eq!(grid(10, 80).cols, 3);
+        assert_eq!(grid(10, 119).cols, 3);
+        assert_eq!(grid(10, 120).cols, 4);
+        assert_eq!(grid(10, 200).cols, 4);
+    }
+
+    #[test]
+    fn few_cards_get_a_column_each() {
+        assert_eq!(grid(0, 200).cols, 1);
+        assert_eq!(grid(2, 200).cols, 2);
+        assert_eq!(grid(3, 200).cols, 3);
+        assert_eq!(grid(3, 60).cols, 2);
+    }
+
+    #[test]
+    fn cards_tile_the_area_row_by_row() {
+        for width in [40, 79, 80, 119, 120, 200] {
+            let grid = grid(10, width);
+            for (i, rect) in grid.rects.iter().enumerate() {
+                let (row, col) = ((i / grid.cols) as u16, i % grid.cols);
+                assert_eq!(rect.y, 7 + (row * CARD_HEIGHT).min(HEIGHT), "width {}", width);
+                assert_eq!(rect.height, CARD_HEIGHT.min(HEIGHT.saturating_sub(row * CARD_HEIGHT)));
+                assert_eq!(rect.x, grid.rects[col].x);
+                assert!(rect.right() <= width);
+            }
+            // Columns share the width without gaps
+            let last = grid.rects[grid.cols - 1];
+            assert_eq!(last.right(), width);
+        }
+    }
+
+    #[test]
+    fn hit_testing_finds_the_drawn_card() {
+        for width in [40, 79, 80, 119, 120, 200] {
+            let grid = grid(10, width);
+            for (i, rect) in grid.rects.iter().enumerate().filter(|(_, r)| r.height > 0) {
+                assert_eq!(grid.card_at(rect.x, rect.y), Some(i));
+                assert_eq!(grid.card_at(rect.right() - 1, rect.bottom() - 1), Some(i));
+            }
+        }
+    }
+
+    #[test]
+    fn hit_testing_misses_outside_the_cards() {
+        let grid = grid(5, 120);
+        // Above the grid, past the ragged last row, below the area
+        assert_eq!(grid.card_at(0, 6), None);
+        assert_eq!(grid.card_at(119, 7 + CARD_HEIGHT), None);
+        assert_eq!(grid.card_at(0, 7 + HEIGHT), None);
+        assert_eq!(grid.card_at(120, 7), None);
+    }
+
+    #[test]
+    fn arrows_move_within_the_grid() {
+        // 3 columns: 0 1 2 / 3 4 5 / 6 7
+        let grid = grid(8, 100);
+        assert_eq!(grid.cols, 3);
+        assert_eq!(grid.right(2), 3);
+        assert_eq!(grid.right(7), 7);
+        assert_eq!(grid.left(3), 2);
+        assert_eq!(grid.left(0), 0);
+        assert_eq!(grid.up(4), 1);
+        assert_eq!(grid.up(1), 1);
+        assert_eq!(grid.down(1), 4);
+        assert_eq!(grid.down(4), 7);
+    }
+
+    #[test]
+    fn down_into_a_ragged_row_lands_on_the_last_card() {
+        let grid = grid(8, 100);
+        assert_eq!(grid.down(5), 7);
+        assert_eq!(grid.down(7), 7);
+        assert_eq!(grid.down(6), 6);
+        assert_eq!(grid.up(7), 4);
+    }
+
+    #[test]
+    fn navigation_survives_an_empty_grid() {
+        let grid = grid(0, 100);
+        assert_eq!(grid.left(0), 0);
+        assert_eq!(grid.right(0), 0);
+        assert_eq!(grid.up(0), 0);
+        assert_eq!(grid.down(0), 0);
+        assert_eq!(grid.card_at(1, 8), None);
+    }
+}
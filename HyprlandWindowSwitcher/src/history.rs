@@ -0,0 +1,266 @@
+// Selection history and frecency ranking
+//
+// Every window focused or app launched from the switcher is recorded by class
+// and title. The frecency of an entry adds up its recent selections, weighted
+// by how long ago they were made, so often and recently used windows sort first.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::state_dir;
+
+// Timestamps kept per entry, older selections only count towards `count`
+const MAX_VISITS: usize = 10;
+// Entries beyond this are pruned, lowest frecency first
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    class: String,
+    title: String,
+    // Total selections, including the ones no longer in `visits`
+    count: u32,
+    // Most recent selections, seconds since the Unix epoch, newest last
+    visits: Vec<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    entries: Vec<Entry>,
+}
+
+fn history_path() -> PathBuf {
+    state_dir().join("history.json")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Weight of a single selection made `age` seconds ago
+fn visit_weight(age: u64) -> f64 {
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+    match age {
+        a if a < 4 * HOUR => 100.0,
+        a if a < DAY => 70.0,
+        a if a < 7 * DAY => 50.0,
+        a if a < 30 * DAY => 30.0,
+        a if a < 90 * DAY => 10.0,
+        _ => 0.0,
+    }
+}
+
+impl Entry {
+    // Mean weight of the sampled visits scaled by the total count, as Firefox does
+    fn frecency(&self, now: u64) -> f64 {
+        if self.visits.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self.visits.iter().map(|&t| visit_weight(now.saturating_sub(t))).sum();
+        sum / self.visits.len() as f64 * self.count as f64
+    }
+}
+
+impl History {
+    pub fn load() -> Self {
+        fs::read_to_string(history_path())
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = history_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    pub fn record(&mut self, class: &str, title: &str) {
+        self.record_at(class, title, now());
+    }
+
+    fn record_at(&mut self, class: &str, title: &str, now: u64) {
+        match self.entries.iter_mut().find(|e| e.class == class && e.title == title) {
+            Some(entry) => {
+                entry.count += 1;
+                entry.visits.push(now);
+                if entry.visits.len() > MAX_VISITS {
+                    entry.visits.remove(0);
+                }
+            }
+            None => self.entries.push(Entry {
+                class: class.to_string(),
+                title: title.to_string(),
+                count: 1,
+                visits: vec![now],
+            }),
+        }
+
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries
+                .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+            self.entries.truncate(MAX_ENTRIES);
+        }
+    }
+
+    /// Frecency of a window: everything recorded for its class, plus the
+    /// exact class and title again so the same browser tab beats a new one
+    pub fn score(&self, class: &str, title: &str) -> f64 {
+        self.score_at(class, title, now())
+    }
+
+    fn score_at(&self, class: &str, title: &str, now: u64) -> f64 {
+        self.entries
+            .iter()
+            .filter(|e| e.class.eq_ignore_ascii_case(class))
+            .map(|e| {
+                let frecency = e.frecency(now);
+                if e.title == title {
+                    frecency * 2.0
+                } else {
+                    frecency
+                }
+            })
+            .sum()
+    }
+}
+
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        s if s < 60 => "just now".to_string(),
+        s if s < 60 * 60 => format!("{}m ago", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+// `--reset`: delete the history file; returns whether there was one
+fn forget(path: &Path) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// `history [--reset]`: list the recorded selections by frecency, or forget them
+pub fn run(args: impl Iterator<Item = String>) -> io::Result<()> {
+    let mut reset = false;
+    for arg in args {
+        match arg.as_str() {
+            "--reset" => reset = true,
+            _ => eprintln!("ignoring unknown argument: {}", arg),
+        }
+    }
+
+    if reset {
+        let path = history_path();
+        if forget(&path)? {
+            println!("Removed {}", path.display());
+        }
+        return Ok(());
+    }
+
+    let history = History::load();
+    if history.entries.is_empty() {
+        println!("No selections recorded yet");
+        return Ok(());
+    }
+
+    let now = now();
+    let mut entries: Vec<&Entry> = history.entries.iter().collect();
+    entries.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+
+    println!("{:>8}  {:>5}  {:>9}  {:<24}  Title", "Score", "Uses", "Last", "Class");
+    for entry in entries {
+        let last = entry.visits.last().map(|&t| format_age(now.saturating_sub(t)));
+        println!(
+            "{:>8.0}  {:>5}  {:>9}  {:<24}  {}",
+            entry.frecency(now),
+            entry.count,
+            last.unwrap_or_default(),
+            entry.class,
+            entry.title
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+    const NOW: u64 = 1_000 * DAY;
+
+    #[test]
+    fn selections_count_less_as_they_age() {
+        let ages = [0, 5 * 60 * 60, 2 * DAY, 10 * DAY, 60 * DAY, 100 * DAY];
+        let weights: Vec<f64> = ages.iter().map(|&age| visit_weight(age)).collect();
+        assert_eq!(weights, [100.0, 70.0, 50.0, 30.0, 10.0, 0.0]);
+
+        let mut history = History::default();
+        history.record_at("firefox", "old", NOW - 10 * DAY);
+        history.record_at("firefox", "old", NOW - 10 * DAY);
+        history.record_at("kitty", "new", NOW);
+        // One fresh selection beats two from last week
+        assert!(history.score_at("kitty", "", NOW) > history.score_at("firefox", "", NOW));
+        assert_eq!(history.score_at("firefox", "", NOW + 100 * DAY), 0.0);
+    }
+
+    #[test]
+    fn the_exact_title_counts_twice_and_class_ignores_case() {
+        let mut history = History::default();
+        history.record_at("firefox", "mail", NOW);
+        assert_eq!(history.score_at("Firefox", "docs", NOW), 100.0);
+        assert_eq!(history.score_at("firefox", "mail", NOW), 200.0);
+    }
+
+    #[test]
+    fn only_the_latest_visits_are_kept_but_all_are_counted() {
+        let mut history = History::default();
+        for i in 0..MAX_VISITS as u64 + 5 {
+            history.record_at("kitty", "zsh", NOW + i);
+        }
+        let entry = &history.entries[0];
+        assert_eq!(entry.count, MAX_VISITS as u32 + 5);
+        assert_eq!(entry.visits.len(), MAX_VISITS);
+        assert_eq!(entry.visits[0], NOW + 5);
+    }
+
+    #[test]
+    fn the_lowest_frecency_entries_are_pruned() {
+        let mut history = History::default();
+        for i in 0..MAX_ENTRIES {
+            history.record_at("app", &i.to_string(), NOW - DAY);
+        }
+        // Too old to count for anything
+        history.entries[7].visits = vec![NOW - 365 * DAY];
+        history.record_at("new", "window", NOW);
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        assert!(history.entries.iter().any(|e| e.class == "new"));
+        assert!(!history.entries.iter().any(|e| e.title == "7"));
+    }
+
+    #[test]
+    fn reset_removes_the_file_once() {
+        let dir = crate::scratch_dir("history");
+        let path = dir.join("history.json");
+        fs::write(&path, "{}").unwrap();
+        assert!(forget(&path).unwrap());
+        assert!(!path.exists());
+        assert!(!forget(&path).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+// Apps that can be launched from the switcher: pinned favourites and
+// desktop entries found in the XDG data directories
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Favourite;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LauncherSource {
+    Favourite,
+    DesktopEntry,
+}
+
+#[derive(Debug, Clone)]
+pub struct Launcher {
+    pub name: String,
+    // Expected window class, used for the icon and to detect running instances
+    pub class: String,
+    pub command: String,
+    pub source: LauncherSource,
+}
+
+impl Launcher {
+    pub fn from_favourite(favourite: &Favourite) -> Self {
+        Launcher {
+            name: favourite.name.clone().unwrap_or_else(|| favourite.class.clone()),
+            class: favourite.class.clone(),
+            command: favourite.command.clone(),
+            source: LauncherSource::Favourite,
+        }
+    }
+
+    // Text the filter is matched against
+    pub fn search_text(&self) -> String {
+        format!("{} {} {}", self.name, self.class, self.command)
+    }
+}
+
+fn application_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home.join(".local/share"));
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    std::iter::once(data_home)
+        .chain(data_dirs.split(':').map(PathBuf::from))
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+// Drop the %f/%U/... field codes from an Exec line; %% is a literal percent
+fn strip_field_codes(exec: &str) -> String {
+    let mut result = String::new();
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        if chars.next() == Some('%') {
+            result.push('%');
+        }
+    }
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parse_desktop_entry(contents: &str) -> Option<Launcher> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut exec = None;
+    let mut class = None;
+    let mut is_application = false;
+    let mut hidden = false;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "Name" => name = Some(value.trim().to_string()),
+            "Exec" => exec = Some(strip_field_codes(value.trim())),
+            "StartupWMClass" => class = Some(value.trim().to_string()),
+            "Type" => is_application = value.trim() == "Application",
+            "NoDisplay" | "Hidden" => hidden |= value.trim() == "true",
+            _ => {}
+        }
+    }
+
+    if !is_application || hidden {
+        return None;
+    }
+    let name = name?;
+    let command = exec.filter(|e| !e.is_empty())?;
+    let class = class.unwrap_or_else(|| {
+        // Without StartupWMClass the binary name is the best guess at the class
+        let program = command.split_whitespace().next().unwrap_or("");
+        program.rsplit('/').next().unwrap_or(program).to_string()
+    });
+    Some(Launcher {
+        name,
+        class,
+        command,
+        source: LauncherSource::DesktopEntry,
+    })
+}
+
+/// All visible desktop entries; earlier directories shadow later ones with the same file name
+pub fn desktop_entries() -> Vec<Launcher> {
+    let mut seen = Vec::new();
+    let mut launchers = Vec::new();
+
+    for dir in application_dirs() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let is_desktop = file_name.to_string_lossy().ends_with(".desktop");
+            if !is_desktop || seen.contains(&file_name) {
+                continue;
+            }
+            seen.push(file_name);
+            if let Some(launcher) = fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|contents| parse_desktop_entry(&contents))
+            {
+                launchers.push(launcher);
+            }
+        }
+    }
+
+    launchers.sort_by(|a, b| a.name.cmp(&b.name));
+    launchers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_codes_are_stripped_and_double_percent_kept() {
+        assert_eq!(strip_field_codes("firefox %u"), "firefox");
+        assert_eq!(strip_field_codes("code --new-window %F"), "code --new-window");
+        assert_eq!(strip_field_codes("vlc %U --started-from-file"), "vlc --started-from-file");
+        assert_eq!(strip_field_codes("printf 100%%"), "printf 100%");
+    }
+
+    #[test]
+    fn entries_are_read_from_the_desktop_entry_group() {
+        let launcher = parse_desktop_entry(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Firefox\n\
+             Name[de]=Feuerfuchs\n\
+             Exec=/usr/lib/firefox/firefox %u\n\
+             \n\
+             [Desktop Action new-window]\n\
+             Name=New Window\n\
+             Exec=/usr/lib/firefox/firefox --new-window %u\n",
+        )
+        .unwrap();
+        assert_eq!(launcher.name, "Firefox");
+        assert_eq!(launcher.command, "/usr/lib/firefox/firefox");
+        // No StartupWMClass, so the class is guessed from the binary
+        assert_eq!(launcher.class, "firefox");
+        assert_eq!(launcher.source, LauncherSource::DesktopEntry);
+    }
+
+    #[test]
+    fn startup_wm_class_wins_over_the_binary_name() {
+        let launcher = parse_desktop_entry(
+            "[Desktop Entry]\nType=Application\nName=Ghostty\nExec=ghostty\nStartupWMClass=com.mitchellh.ghostty\n",
+        )
+        .unwrap();
+        assert_eq!(launcher.class, "com.mitchellh.ghostty");
+    }
+
+    #[test]
+    fn hidden_and_non_application_entries_are_skipped() {
+        let entry = |extra: &str| format!("[Desktop Entry]\nName=Tool\nExec=tool\n{extra}\n");
+        assert!(parse_desktop_entry(&entry("Type=Application")).is_some());
+        assert!(parse_desktop_entry(&entry("Type=Application\nNoDisplay=true")).is_none());
+        assert!(parse_desktop_entry(&entry("Type=Application\nHidden=true")).is_none());
+        assert!(parse_desktop_entry(&entry("Type=Link")).is_none());
+        assert!(parse_desktop_entry(&entry("Type=Application\nExec=%f")).is_none());
+    }
+}
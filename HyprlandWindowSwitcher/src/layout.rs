@@ -0,0 +1,299 @@
+// Screen layout outside the card grid: header and strip regions, workspace
+// slots and the minimap, with hit testing for the mouse
+
+use ratatui::layout::{Constraint, Layout, Rect};
+
+use crate::app::{App, HitTarget, SortColumn, View};
+use crate::model::Workspace;
+use crate::text;
+
+// Screen regions: header, workspace strip and the card grid
+pub fn split_screen(area: Rect) -> (Rect, Rect, Rect) {
+    // Title plus up to three lines of key help
+    let chunks = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Length(3),
+        Constraint::Min(0),
+    ])
+    .split(area);
+    (chunks[0], chunks[1], chunks[2])
+}
+
+pub fn workspace_slot_label(ws: &Workspace) -> String {
+    format!(" 󰋁 {} ({}) ", ws.name, ws.windows)
+}
+
+// Monitor labels and workspace slot rects (with their workspace index) inside the strip
+pub struct StripLayout {
+    pub labels: Vec<(Rect, String)>,
+    pub slots: Vec<(Rect, usize)>,
+}
+
+/// Positions of the workspace slots inside the strip, grouped per monitor
+pub fn workspace_slots(app: &App, area: Rect) -> StripLayout {
+    let inner = Rect {
+        x: area.x + 1,
+        y: area.y + 1,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2).min(1),
+    };
+    let right = inner.x + inner.width;
+    let mut labels = Vec::new();
+    let mut slots = Vec::new();
+    let mut x = inner.x + 1;
+    let mut current_monitor: Option<&str> = None;
+
+    for (i, ws) in app.workspaces.iter().enumerate() {
+        if current_monitor != Some(ws.monitor.as_str()) {
+            current_monitor = Some(&ws.monitor);
+            let label = format!("{}:", ws.monitor);
+            let width = text::display_width(&label) as u16;
+            if x + width > right {
+                break;
+            }
+            labels.push((Rect::new(x, inner.y, width, inner.height), label));
+            x += width + 1;
+        }
+
+        let width = text::display_width(&workspace_slot_label(ws)) as u16;
+        if x + width > right {
+            break;
+        }
+        slots.push((Rect::new(x, inner.y, width, inner.height), i));
+        x += width + 1;
+    }
+
+    StripLayout { labels, slots }
+}
+
+// Axis-aligned rectangle in minimap coordinates (layout pixels, y growing downwards)
+#[derive(Debug, Clone, Copy)]
+pub struct MapRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl MapRect {
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+// Gap between workspace panels, as a fraction of the widest monitor
+const MINIMAP_GAP: f64 = 0.05;
+
+/// Every workspace that holds windows, drawn as a scaled-down copy of its
+/// monitor placed side by side, with the windows at their real positions
+pub struct MinimapLayout {
+    pub panels: Vec<(MapRect, String)>,
+    pub windows: Vec<(usize, MapRect)>,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl MinimapLayout {
+    pub fn new(app: &App) -> Self {
+        let mut panels = Vec::new();
+        let mut windows = Vec::new();
+        let gap = app
+            .monitors
+            .iter()
+            .map(|m| m.logical_size().0)
+            .fold(0.0, f64::max)
+            * MINIMAP_GAP;
+        let mut offset = 0.0;
+        let mut height: f64 = 0.0;
+
+        for ws in &app.workspaces {
+            let members: Vec<usize> = (0..app.visible.len())
+                .filter(|&pos| app.windows[app.visible[pos]].workspace_id == ws.id)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            let Some(monitor) = app.monitors.iter().find(|m| m.name == ws.monitor) else {
+                continue;
+            };
+            let (width, mon_height) = monitor.logical_size();
+
+            panels.push((
+                MapRect { x: offset, y: 0.0, width, height: mon_height },
+                format!("{} ({})", ws.name, monitor.name),
+            ));
+            for i in members {
+                let win = &app.windows[app.visible[i]];
+                let origin = app
+                    .monitors
+                    .iter()
+                    .find(|m| m.id == win.monitor)
+                    .unwrap_or(monitor);
+                windows.push((
+                    i,
+                    MapRect {
+                        x: offset + (win.at.0 - origin.x) as f64,
+                        y: (win.at.1 - origin.y) as f64,
+                        width: win.size.0 as f64,
+                        height: win.size.1 as f64,
+                    },
+                ));
+            }
+            offset += width + gap;
+            height = height.max(mon_height);
+        }
+
+        MinimapLayout {
+            panels,
+            windows,
+            width: (offset - gap).max(1.0),
+            height: height.max(1.0),
+        }
+    }
+
+    pub fn window_rect(&self, index: usize) -> Option<MapRect> {
+        self.windows.iter().find(|(i, _)| *i == index).map(|(_, r)| *r)
+    }
+
+    /// Map a terminal cell inside `area` back to the window drawn there.
+    /// Smaller windows win so floating windows above tiled ones can be picked.
+    pub fn hit_test(&self, mx: u16, my: u16, area: Rect) -> Option<usize> {
+        if area.width == 0 || area.height == 0 || !area.contains((mx, my).into()) {
+            return None;
+        }
+        let x = (mx - area.x) as f64 + 0.5;
+        let y = (my - area.y) as f64 + 0.5;
+        let x = x / area.width as f64 * self.width;
+        let y = y / area.height as f64 * self.height;
+
+        self.windows
+            .iter()
+            .filter(|(_, rect)| rect.contains(x, y))
+            .min_by(|a, b| (a.1.width * a.1.height).total_cmp(&(b.1.width * b.1.height)))
+            .map(|(i, _)| *i)
+    }
+}
+
+// Selection marker and icon in front of every list row
+pub const LIST_ICON_WIDTH: u16 = 3;
+
+// Narrowest title column before the list drops its right-hand columns
+const MIN_LIST_TITLE: u16 = 12;
+
+/// Columns and rows of the list view: an optional header line with one cell
+/// per sortable column, then one row per item from the scroll position down
+pub struct ListLayout {
+    pub header: Option<Rect>,
+    /// Cells of the shown columns on the header line, in display order; rows
+    /// use the same x and width
+    pub columns: Vec<(SortColumn, Rect)>,
+    /// Item index and row of every item in sight
+    pub rows: Vec<(usize, Rect)>,
+}
+
+impl ListLayout {
+    pub fn new(app: &App, area: Rect) -> Self {
+        ListLayout::build(app, area, true)
+    }
+
+    /// Rows only, for the inline list under its prompt
+    pub fn without_header(app: &App, area: Rect) -> Self {
+        ListLayout::build(app, area, false)
+    }
+
+    fn build(app: &App, area: Rect, header: bool) -> Self {
+        let class = 18.min(area.width / 4);
+        // Workspace and state give way to the title on narrow terminals
+        let mut widths = vec![
+            (SortColumn::Class, class),
+            (SortColumn::Title, 0),
+            (SortColumn::Workspace, 12),
+            (SortColumn::State, 14),
+        ];
+        let title = loop {
+            let used: u16 = widths.iter().map(|(_, w)| w + 1).sum();
+            let title = area.width.saturating_sub(LIST_ICON_WIDTH + used);
+            if title >= MIN_LIST_TITLE || widths.len() == 2 {
+                break title;
+            }
+            widths.pop();
+        };
+        let mut x = area.x + LIST_ICON_WIDTH;
+        let top = if header { area.y + 1 } else { area.y };
+        let columns = widths
+            .into_iter()
+            .map(|(column, width)| {
+                let width = if column == SortColumn::Title { title } else { width };
+                let cell = Rect::new(x, area.y, width, 1);
+                x += width + 1;
+                (column, cell)
+            })
+            .collect();
+
+        // Scroll only as far as it takes to bring the selection into sight,
+        // so pointing at a row never moves the list under the pointer
+        let count = app.item_count();
+        let height = (area.y + area.height).saturating_sub(top) as usize;
+        let selected = app.selected_index;
+        let mut scroll = app.list_scroll.get().min(count.saturating_sub(height));
+        if selected < scroll {
+            scroll = selected;
+        } else if height > 0 && selected >= scroll + height {
+            scroll = selected + 1 - height;
+        }
+        app.list_scroll.set(scroll);
+        let rows = (scroll..count)
+            .take(height)
+            .enumerate()
+            .map(|(row, i)| (i, Rect::new(area.x, top + row as u16, area.width, 1)))
+            .collect();
+
+        ListLayout {
+            header: header.then(|| Rect { height: 1.min(area.height), ..area }),
+            columns,
+            rows,
+        }
+    }
+
+    /// Column whose header is at a terminal cell
+    pub fn column_at(&self, x: u16, y: u16) -> Option<SortColumn> {
+        self.header.filter(|h| h.height > 0 && y == h.y)?;
+        self.columns.iter().find(|(_, cell)| x >= cell.x && x <= cell.x + cell.width).map(|(c, _)| *c)
+    }
+
+    /// Item on the row at a terminal cell
+    pub fn row_at(&self, x: u16, y: u16) -> Option<usize> {
+        self.rows.iter().find(|(_, row)| row.contains((x, y).into())).map(|(i, _)| *i)
+    }
+}
+
+/// Card under a terminal cell, using the grid render_cards drew
+pub fn hit_test(app: &App, mx: u16, my: u16, area: Rect) -> Option<usize> {
+    app.grid_layout(app.item_count(), area).card_at(mx, my)
+}
+
+/// Hit test across the workspace strip and the card grid
+pub fn hit_target(app: &App, mx: u16, my: u16, size: Rect) -> Option<HitTarget> {
+    let (_, strip, grid) = split_screen(size);
+    let on_slot = workspace_slots(app, strip).slots.into_iter().find(|(rect, _)| {
+        mx >= rect.x && mx < rect.x + rect.width && my >= rect.y && my < rect.y + rect.height
+    });
+    if let Some((_, i)) = on_slot {
+        return Some(HitTarget::Workspace(i));
+    }
+    window_at(app, mx, my, grid).map(HitTarget::Card)
+}
+
+/// Window drawn at a terminal cell in the current view
+pub fn window_at(app: &App, mx: u16, my: u16, area: Rect) -> Option<usize> {
+    match app.view {
+        View::Grid => hit_test(app, mx, my, area),
+        View::List => ListLayout::new(app, area).row_at(mx, my),
+        View::Minimap => MinimapLayout::new(app).hit_test(mx, my, area),
+        View::Workspaces | View::Monitors => None,
+    }
+}
@@ -0,0 +1,48 @@
+// Hyprland window switcher as a library: window model, hyprctl access,
+// layout, app state and rendering. main.rs only sets up the terminal and runs
+// the event loop, so all of this can be driven from tests.
+
+pub mod actions;
+pub mod app;
+pub mod backend;
+pub mod bench;
+pub mod config;
+pub mod daemon;
+pub mod events;
+pub mod grid;
+pub mod history;
+pub mod launcher;
+pub mod layout;
+pub mod marks;
+pub mod model;
+pub mod popup;
+pub mod rewrite;
+pub mod rules;
+pub mod session;
+pub mod stats;
+pub mod text;
+pub mod ui;
+pub mod worker;
+
+use std::path::PathBuf;
+
+// Directory for files the switcher keeps between runs (sessions, history, ...)
+pub fn state_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| {
+            let home = std::env::var_os("HOME").unwrap_or_default();
+            PathBuf::from(home).join(".local/state")
+        });
+    base.join("hypr_window_switcher")
+}
+
+/// Fresh directory under the system temp dir for tests that touch files,
+/// unique per `name` and test process
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("hypr_window_switcher-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create scratch dir");
+    dir
+}
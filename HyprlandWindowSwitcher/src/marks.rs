@@ -0,0 +1,149 @@
+// Vim-like window marks: `m<letter>` remembers a window, `'<letter>` jumps back to it
+//
+// Marks are keyed by address, with the class and title kept as a fallback so a
+// mark still finds the app after it restarts and gets a new address.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{get_windows, try_dispatch};
+use crate::model::Window;
+use crate::state_dir;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mark {
+    pub address: String,
+    pub class: String,
+    pub title: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Marks {
+    marks: BTreeMap<char, Mark>,
+}
+
+fn marks_path() -> PathBuf {
+    state_dir().join("marks.json")
+}
+
+// Letters and digits can be used as mark names
+pub fn is_mark_key(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+}
+
+impl Marks {
+    pub fn load() -> Self {
+        fs::read_to_string(marks_path())
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = marks_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    pub fn set(&mut self, key: char, win: &Window) {
+        self.marks.insert(
+            key,
+            Mark {
+                address: win.id.clone(),
+                class: win.class.clone(),
+                title: win.title.clone(),
+            },
+        );
+    }
+
+    /// Window a mark points at: same address, else same class and title,
+    /// else the first window of the same class
+    pub fn resolve<'a>(&self, key: char, windows: &'a [Window]) -> Option<&'a Window> {
+        let mark = self.marks.get(&key)?;
+        windows
+            .iter()
+            .find(|w| w.id == mark.address)
+            .or_else(|| windows.iter().find(|w| w.class == mark.class && w.title == mark.title))
+            .or_else(|| windows.iter().find(|w| w.class == mark.class))
+    }
+
+    /// Mark letters currently resolving to `win`, for the card badge
+    pub fn keys_for(&self, win: &Window, windows: &[Window]) -> String {
+        self.marks
+            .keys()
+            .filter(|&&key| self.resolve(key, windows).is_some_and(|w| w.id == win.id))
+            .collect()
+    }
+}
+
+/// `jump <letter>`: focus the marked window, for use from bindings.conf
+pub fn jump(key: &str) -> io::Result<()> {
+    let mut chars = key.chars();
+    let (Some(key), None) = (chars.next(), chars.next()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "usage: hypr_window_switcher jump <letter>",
+        ));
+    };
+
+    let mut marks = Marks::load();
+    let windows = get_windows()?;
+    let Some(win) = marks.resolve(key, &windows) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("mark '{}' does not match any window", key),
+        ));
+    };
+
+    try_dispatch(&["focuswindow".to_string(), format!("address:{}", win.id)])?;
+    // Follow the window to its new address if it was found by class/title
+    marks.set(key, win);
+    marks.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marked(win: &Window) -> Marks {
+        let mut marks = Marks::default();
+        marks.set('a', win);
+        marks
+    }
+
+    #[test]
+    fn the_address_wins_over_class_and_title() {
+        let marks = marked(&Window::new("0x2", "kitty", "logs", 1));
+        let windows = [Window::new("0x1", "kitty", "logs", 1), Window::new("0x2", "kitty", "zsh", 1)];
+        assert_eq!(marks.resolve('a', &windows).map(|w| w.id.as_str()), Some("0x2"));
+    }
+
+    #[test]
+    fn a_restarted_app_is_found_by_class_and_title() {
+        let marks = marked(&Window::new("0x9", "kitty", "logs", 1));
+        let windows = [Window::new("0x1", "kitty", "zsh", 1), Window::new("0x2", "kitty", "logs", 1)];
+        assert_eq!(marks.resolve('a', &windows).map(|w| w.id.as_str()), Some("0x2"));
+    }
+
+    #[test]
+    fn any_window_of_the_class_is_the_last_resort() {
+        let marks = marked(&Window::new("0x9", "kitty", "logs", 1));
+        let windows = [Window::new("0x1", "firefox", "logs", 1), Window::new("0x2", "kitty", "zsh", 1)];
+        assert_eq!(marks.resolve('a', &windows).map(|w| w.id.as_str()), Some("0x2"));
+    }
+
+    #[test]
+    fn nothing_matches_without_the_class_or_the_mark() {
+        let marks = marked(&Window::new("0x9", "kitty", "logs", 1));
+        let windows = [Window::new("0x1", "firefox", "logs", 1)];
+        assert!(marks.resolve('a', &windows).is_none());
+        assert!(marks.resolve('b', &windows).is_none());
+    }
+}
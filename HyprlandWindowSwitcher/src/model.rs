@@ -0,0 +1,229 @@
+// Window model: what hyprctl reports about windows, monitors and workspaces,
+// plus the matching used to filter them
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Window {
+    pub id: String,
+    pub class: String,
+    pub title: String,
+    pub workspace: String,
+    pub workspace_id: i64,
+    pub monitor: i64,
+    // Position and size in layout coordinates, as reported by Hyprland
+    pub at: (i64, i64),
+    pub size: (i64, i64),
+    pub floating: bool,
+    pub pid: i64,
+    pub initial_class: String,
+    // 0 for the focused window, counting up for windows focused longer ago
+    pub focus_history: i64,
+    pub pinned: bool,
+    pub fullscreen: bool,
+    // Inactive tab of a group
+    pub hidden: bool,
+    // Hyprland tags, from window rules or `tagwindow`; missing in snapshots
+    // from before tags were read
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Window {
+    /// A tiled window on `workspace_id` with no process, geometry or state
+    /// beyond its class and title; what tests and benchmarks start from
+    pub fn new(id: &str, class: &str, title: &str, workspace_id: i64) -> Self {
+        Window {
+            id: id.to_string(),
+            class: class.to_string(),
+            title: title.to_string(),
+            workspace: workspace_id.to_string(),
+            workspace_id,
+            monitor: 0,
+            at: (0, 0),
+            size: (0, 0),
+            floating: false,
+            pid: -1,
+            initial_class: class.to_string(),
+            focus_history: 0,
+            pinned: false,
+            fullscreen: false,
+            hidden: false,
+            tags: Vec::new(),
+        }
+    }
+
+    // Whether a tag starts with `prefix`, ignoring case, so `#wo` already matches "work"
+    pub fn has_tag(&self, prefix: &str) -> bool {
+        let prefix = prefix.to_lowercase();
+        self.tags.iter().any(|tag| tag.to_lowercase().starts_with(&prefix))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Monitor {
+    pub id: i64,
+    pub name: String,
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    pub scale: f64,
+    pub refresh_rate: f64,
+    pub focused: bool,
+    pub active_workspace_id: i64,
+    pub active_workspace: String,
+}
+
+impl Monitor {
+    /// An unfocused monitor at the origin, at scale 1 and showing workspace
+    /// 1; the monitor counterpart of `Window::new`
+    pub fn new(id: i64, name: &str, width: i64, height: i64) -> Self {
+        Monitor {
+            id,
+            name: name.to_string(),
+            x: 0,
+            y: 0,
+            width,
+            height,
+            scale: 1.0,
+            refresh_rate: 60.0,
+            focused: false,
+            active_workspace_id: 1,
+            active_workspace: "1".to_string(),
+        }
+    }
+
+    // Size in layout coordinates, which is what client positions are expressed in
+    pub fn logical_size(&self) -> (f64, f64) {
+        let scale = if self.scale > 0.0 { self.scale } else { 1.0 };
+        (self.width as f64 / scale, self.height as f64 / scale)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+    pub monitor: String,
+    pub windows: usize,
+}
+
+impl Workspace {
+    // Argument accepted by workspace dispatchers; special workspaces are addressed by name
+    pub fn dispatch_target(&self) -> String {
+        if self.id < 0 {
+            self.name.clone()
+        } else {
+            self.id.to_string()
+        }
+    }
+}
+
+// Unread counter a chat or mail client keeps in its title, like "(3) Slack"
+// or "Inbox [12]"
+pub fn unread_count(title: &str) -> Option<u32> {
+    let title = title.trim();
+    let parse = |n: &str| n.trim_end_matches('+').parse::<u32>().ok().filter(|&n| n > 0);
+    [('(', ')'), ('[', ']')].into_iter().find_map(|(open, close)| {
+        let leading = title
+            .strip_prefix(open)
+            .and_then(|rest| rest.split_once(close))
+            .and_then(|(n, _)| parse(n));
+        let trailing = title
+            .strip_suffix(close)
+            .and_then(|rest| rest.rsplit_once(open))
+            .and_then(|(_, n)| parse(n));
+        leading.or(trailing)
+    })
+}
+
+/// Split filter text into `#tag` words and the text matched against class and title
+pub fn split_tag_filter(pattern: &str) -> (Vec<&str>, String) {
+    let (tags, words): (Vec<&str>, Vec<&str>) = pattern
+        .split_whitespace()
+        .partition(|word| word.len() > 1 && word.starts_with('#'));
+    (tags.into_iter().map(|tag| &tag[1..]).collect(), words.join(" "))
+}
+
+/// Tag edits typed for `tagwindow`, split at spaces and commas: `name`
+/// toggles a tag, `+name` adds and `-name` removes it. Fails on the first
+/// name with anything but letters, digits, `_`, `-` and `.`, so input can't
+/// add commands to the `;`-separated batch it ends up in.
+pub fn parse_tag_edits(input: &str) -> Result<Vec<&str>, String> {
+    let mut edits = Vec::new();
+    for edit in input.split(|c: char| c.is_whitespace() || c == ',') {
+        let edit = edit.trim_start_matches('#');
+        let name = edit.strip_prefix(['+', '-']).unwrap_or(edit);
+        if name.is_empty() {
+            continue;
+        }
+        if !name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+            return Err(format!("invalid tag {:?}", name));
+        }
+        edits.push(edit);
+    }
+    Ok(edits)
+}
+
+/// Case-insensitive subsequence match of `pattern` in `text`. Higher scores
+/// mean tighter matches: consecutive characters and word starts earn bonuses.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for p in pattern.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (pos..text.len()).find(|&i| text[i] == p)?;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(found);
+        pos = found + 1;
+    }
+
+    // Without a pattern everything matches equally, keeping the original order
+    if last_match.is_none() {
+        return Some(0);
+    }
+    // Prefer shorter texts when the match quality is otherwise equal
+    Some(score * 100 - text.len() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unread_counters_at_either_end_of_the_title() {
+        assert_eq!(unread_count("(3) Slack | general"), Some(3));
+        assert_eq!(unread_count("Inbox [12]"), Some(12));
+        assert_eq!(unread_count(" [99+] Discord "), Some(99));
+        assert_eq!(unread_count("Signal (1)"), Some(1));
+    }
+
+    #[test]
+    fn titles_without_a_counter_have_none() {
+        assert_eq!(unread_count("(0) Slack"), None);
+        assert_eq!(unread_count("main.rs (hypr_window_switcher)"), None);
+        assert_eq!(unread_count("Page 3 of 10"), None);
+        assert_eq!(unread_count("(3"), None);
+    }
+
+    #[test]
+    fn tag_edits_keep_their_sign() {
+        assert_eq!(parse_tag_edits("work, +rust -old #fav + -"), Ok(vec!["work", "+rust", "-old", "fav"]));
+    }
+
+    #[test]
+    fn tag_edits_cannot_smuggle_commands() {
+        assert!(parse_tag_edits("a;dispatch exit").is_err());
+        assert!(parse_tag_edits("+x*").is_err());
+        assert_eq!(parse_tag_edits("ok -a;b"), Err("invalid tag \"a;b\"".to_string()));
+    }
+}
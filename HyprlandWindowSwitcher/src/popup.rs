@@ -0,0 +1,61 @@
+// Popup behaviour: find the terminal window the switcher runs in so it can be
+// left out of the list and so focus moving away from it can close the switcher
+
+use std::fs;
+
+use serde_json::Value;
+
+use crate::backend::hyprctl;
+use crate::model::Window;
+
+/// Terminal title set at startup, unique per switcher process
+pub fn host_title() -> String {
+    format!("hypr_window_switcher:{}", std::process::id())
+}
+
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name in parentheses may contain spaces, the fields after it do not
+    let (_, rest) = stat.rsplit_once(") ")?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+// Our parent, its parent and so on up to init
+fn ancestor_pids() -> Vec<i64> {
+    let mut pids = Vec::new();
+    let mut pid = std::process::id();
+    while let Some(parent) = parent_pid(pid).filter(|&p| p > 1) {
+        pids.push(parent as i64);
+        pid = parent;
+    }
+    pids
+}
+
+// Address of the focused window, if it is the switcher's terminal
+fn active_host(ancestors: &[i64], title: &str) -> Option<String> {
+    let output = hyprctl(&["activewindow", "-j"]).ok()?;
+    let active: Value = serde_json::from_slice(&output).ok()?;
+    let is_host = active["pid"].as_i64().is_some_and(|pid| ancestors.contains(&pid))
+        || active["title"].as_str() == Some(title);
+    is_host
+        .then(|| active["address"].as_str().map(str::to_string))
+        .flatten()
+}
+
+/// Address of the window hosting the switcher: the one carrying our title,
+/// else the only window owned by one of our ancestor processes
+pub fn find_host(windows: &[Window]) -> Option<String> {
+    let title = host_title();
+    if let Some(win) = windows.iter().find(|w| w.title == title) {
+        return Some(win.id.clone());
+    }
+    let ancestors = ancestor_pids();
+    let mut owned = windows.iter().filter(|w| ancestors.contains(&w.pid));
+    if let (Some(win), None) = (owned.next(), owned.next()) {
+        return Some(win.id.clone());
+    }
+    // Single-instance terminals own all their windows from one pid, and the
+    // daemon's snapshot may predate our window; either way the terminal we
+    // were just started in has focus
+    active_host(&ancestors, &title)
+}
@@ -0,0 +1,151 @@
+// Display names for cards: class aliases and regex title rewrites from the
+// config. Only what is drawn changes; filtering still sees the raw class and title.
+
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use crate::config::Config;
+
+struct Rule {
+    class: Option<Regex>,
+    pattern: Regex,
+    replace: String,
+}
+
+pub struct Rewriter {
+    aliases: BTreeMap<String, String>,
+    rules: Vec<Rule>,
+    // Rewrites skipped for an invalid regex, and why
+    pub warnings: Vec<String>,
+}
+
+fn compile(regex: &str, warnings: &mut Vec<String>) -> Option<Regex> {
+    Regex::new(regex)
+        .map_err(|e| warnings.push(format!("ignoring title rewrite {:?}: {}", regex, e)))
+        .ok()
+}
+
+impl Rewriter {
+    pub fn new(config: &Config) -> Self {
+        let mut warnings = Vec::new();
+        let rules = config
+            .rewrites
+            .iter()
+            .filter_map(|rewrite| {
+                let class = match &rewrite.class {
+                    Some(class) => Some(compile(class, &mut warnings)?),
+                    None => None,
+                };
+                Some(Rule {
+                    class,
+                    pattern: compile(&rewrite.pattern, &mut warnings)?,
+                    replace: rewrite.replace.clone(),
+                })
+            })
+            .collect();
+        Rewriter {
+            aliases: config.aliases.clone(),
+            rules,
+            warnings,
+        }
+    }
+
+    /// Alias for a window class, or the class itself
+    pub fn class_name<'a>(&'a self, class: &'a str) -> &'a str {
+        self.aliases
+            .get(class)
+            .or_else(|| {
+                self.aliases
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(class))
+                    .map(|(_, alias)| alias)
+            })
+            .map(String::as_str)
+            .unwrap_or(class)
+    }
+
+    /// Title after every rule for the class has been applied in turn
+    pub fn title(&self, class: &str, title: &str) -> String {
+        let mut result = title.to_string();
+        for rule in &self.rules {
+            if rule.class.as_ref().is_some_and(|re| !re.is_match(class)) {
+                continue;
+            }
+            result = rule.pattern.replace_all(&result, rule.replace.as_str()).into_owned();
+        }
+        let trimmed = result.trim();
+        // A rule that eats the whole title is not worth an empty card
+        if trimmed.is_empty() {
+            title.to_string()
+        } else {
+            trimmed.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TitleRewrite;
+
+    fn rewrite(class: Option<&str>, pattern: &str, replace: &str) -> TitleRewrite {
+        TitleRewrite {
+            class: class.map(str::to_string),
+            pattern: pattern.to_string(),
+            replace: replace.to_string(),
+        }
+    }
+
+    fn rewriter(rewrites: Vec<TitleRewrite>) -> Rewriter {
+        let mut config = Config::default();
+        config.aliases.insert("com.mitchellh.ghostty".to_string(), "Ghostty".to_string());
+        config.rewrites = rewrites;
+        Rewriter::new(&config)
+    }
+
+    #[test]
+    fn aliases_match_the_class_ignoring_case() {
+        let rewriter = rewriter(Vec::new());
+        assert_eq!(rewriter.class_name("com.mitchellh.ghostty"), "Ghostty");
+        assert_eq!(rewriter.class_name("com.mitchellh.Ghostty"), "Ghostty");
+        assert_eq!(rewriter.class_name("kitty"), "kitty");
+    }
+
+    #[test]
+    fn rules_run_in_order_with_capture_groups() {
+        let rewriter = rewriter(vec![
+            rewrite(None, r"^(?:● )?(.*?) - (.*?) - Visual Studio Code$", "$1 ($2)"),
+            rewrite(None, r"\(hypr_window_switcher\)", "(hws)"),
+        ]);
+        assert_eq!(
+            rewriter.title("code", "● main.rs - hypr_window_switcher - Visual Studio Code"),
+            "main.rs (hws)"
+        );
+    }
+
+    #[test]
+    fn the_class_regex_limits_a_rule_to_some_apps() {
+        let rewriter = rewriter(vec![rewrite(Some("^firefox$"), " — Mozilla Firefox$", "")]);
+        assert_eq!(rewriter.title("firefox", "Inbox — Mozilla Firefox"), "Inbox");
+        assert_eq!(rewriter.title("librewolf", "Inbox — Mozilla Firefox"), "Inbox — Mozilla Firefox");
+    }
+
+    #[test]
+    fn a_rule_eating_the_whole_title_keeps_the_original() {
+        let rewriter = rewriter(vec![rewrite(None, ".*", " ")]);
+        assert_eq!(rewriter.title("kitty", "zsh"), "zsh");
+    }
+
+    #[test]
+    fn invalid_regexes_are_skipped() {
+        let rewriter = rewriter(vec![
+            rewrite(None, "(unclosed", "x"),
+            rewrite(Some("[bad"), "zsh", "x"),
+            rewrite(None, "^zsh$", "shell"),
+        ]);
+        assert_eq!(rewriter.rules.len(), 1);
+        assert_eq!(rewriter.warnings.len(), 2);
+        assert_eq!(rewriter.title("kitty", "zsh"), "shell");
+    }
+}
@@ -0,0 +1,377 @@
+// Window rule builder: picks effects for one window, shows the `windowrule`
+// lines they make and appends them to a Hyprland config file
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use crate::model::{Monitor, Window};
+
+// Which window property the rule matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchBy {
+    Class,
+    Title,
+    InitialClass,
+}
+
+impl MatchBy {
+    pub fn key(self) -> &'static str {
+        match self {
+            MatchBy::Class => "class",
+            MatchBy::Title => "title",
+            MatchBy::InitialClass => "initialClass",
+        }
+    }
+}
+
+// Rows of the builder, top to bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleField {
+    Match,
+    Float,
+    Size,
+    Move,
+    Workspace,
+    Opacity,
+    File,
+    Reload,
+}
+
+impl RuleField {
+    pub const ALL: [RuleField; 8] = [
+        RuleField::Match,
+        RuleField::Float,
+        RuleField::Size,
+        RuleField::Move,
+        RuleField::Workspace,
+        RuleField::Opacity,
+        RuleField::File,
+        RuleField::Reload,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RuleField::Match => "Match",
+            RuleField::Float => "Float",
+            RuleField::Size => "Size",
+            RuleField::Move => "Move",
+            RuleField::Workspace => "Workspace",
+            RuleField::Opacity => "Opacity",
+            RuleField::File => "Append to",
+            RuleField::Reload => "Reload",
+        }
+    }
+}
+
+// An effect with an argument, written only while enabled
+pub struct Effect {
+    pub enabled: bool,
+    pub value: String,
+}
+
+impl Effect {
+    fn off(value: String) -> Self {
+        Effect { enabled: false, value }
+    }
+}
+
+pub struct RuleBuilder {
+    // Address of the window the rule was built from
+    pub window: String,
+    pub match_by: MatchBy,
+    pub pattern: String,
+    pub float: bool,
+    pub size: Effect,
+    pub position: Effect,
+    pub workspace: Effect,
+    pub opacity: Effect,
+    pub files: Vec<PathBuf>,
+    pub file: usize,
+    pub reload: bool,
+    // Selected row, an index into RuleField::ALL
+    pub field: usize,
+    class: String,
+    title: String,
+    initial_class: String,
+}
+
+// Regex matching exactly `text`. Only characters special outside a class
+// are escaped, so `gnome-calculator` stays readable; Hyprland splits rules at
+// commas, so they are matched as any character instead
+fn exact(text: &str) -> String {
+    let mut pattern = String::from("^(");
+    for c in text.chars() {
+        match c {
+            ',' => pattern.push('.'),
+            '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' => {
+                pattern.push('\\');
+                pattern.push(c);
+            }
+            _ => pattern.push(c),
+        }
+    }
+    pattern.push_str(")$");
+    pattern
+}
+
+impl RuleBuilder {
+    /// Builder for `win`, prefilled with its class and current geometry.
+    /// `monitor` is the one it is on: window rules place windows relative to it
+    pub fn new(win: &Window, monitor: Option<&Monitor>, files: Vec<PathBuf>, reload: bool) -> Self {
+        let origin = monitor.map_or((0, 0), |m| (m.x, m.y));
+        RuleBuilder {
+            window: win.id.clone(),
+            match_by: MatchBy::Class,
+            pattern: exact(&win.class),
+            float: false,
+            size: Effect::off(format!("{} {}", win.size.0, win.size.1)),
+            position: Effect::off(format!("{} {}", win.at.0 - origin.0, win.at.1 - origin.1)),
+            workspace: Effect::off(win.workspace.clone()),
+            opacity: Effect::off("0.9".to_string()),
+            files,
+            file: 0,
+            reload,
+            field: 0,
+            class: win.class.clone(),
+            title: win.title.clone(),
+            initial_class: win.initial_class.clone(),
+        }
+    }
+
+    pub fn selected(&self) -> RuleField {
+        RuleField::ALL[self.field]
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        self.files.get(self.file).map(PathBuf::as_path)
+    }
+
+    /// The `windowrule` lines for the enabled effects, one effect per line
+    pub fn lines(&self) -> Vec<String> {
+        let matcher = format!("{}:{}", self.match_by.key(), self.pattern);
+        let mut effects = Vec::new();
+        if self.float {
+            effects.push("float".to_string());
+        }
+        for (name, effect) in [
+            ("size", &self.size),
+            ("move", &self.position),
+            ("workspace", &self.workspace),
+            ("opacity", &self.opacity),
+        ] {
+            if effect.enabled && !effect.value.trim().is_empty() {
+                effects.push(format!("{} {}", name, effect.value.trim()));
+            }
+        }
+        effects
+            .into_iter()
+            .map(|effect| format!("windowrule = {}, {}", effect, matcher))
+            .collect()
+    }
+
+    pub fn next_field(&mut self) {
+        self.field = (self.field + 1).min(RuleField::ALL.len() - 1);
+    }
+
+    pub fn previous_field(&mut self) {
+        self.field = self.field.saturating_sub(1);
+    }
+
+    fn effect_mut(&mut self) -> Option<&mut Effect> {
+        match self.selected() {
+            RuleField::Size => Some(&mut self.size),
+            RuleField::Move => Some(&mut self.position),
+            RuleField::Workspace => Some(&mut self.workspace),
+            RuleField::Opacity => Some(&mut self.opacity),
+            _ => None,
+        }
+    }
+
+    /// Left/Right: step through match kinds or target files, or switch the
+    /// selected effect or option on or off
+    pub fn cycle(&mut self, forward: bool) {
+        match self.selected() {
+            RuleField::Match => {
+                self.match_by = match (self.match_by, forward) {
+                    (MatchBy::Class, true) | (MatchBy::InitialClass, false) => MatchBy::Title,
+                    (MatchBy::Title, true) | (MatchBy::Class, false) => MatchBy::InitialClass,
+                    (MatchBy::InitialClass, true) | (MatchBy::Title, false) => MatchBy::Class,
+                };
+                self.pattern = exact(match self.match_by {
+                    MatchBy::Class => &self.class,
+                    MatchBy::Title => &self.title,
+                    MatchBy::InitialClass => &self.initial_class,
+                });
+            }
+            RuleField::File if !self.files.is_empty() => {
+                let count = self.files.len();
+                self.file = if forward { (self.file + 1) % count } else { (self.file + count - 1) % count };
+            }
+            RuleField::Float => self.float = !self.float,
+            RuleField::Reload => self.reload = !self.reload,
+            _ => {
+                if let Some(effect) = self.effect_mut() {
+                    effect.enabled = !effect.enabled;
+                }
+            }
+        }
+    }
+
+    /// Typing into the pattern or an effect argument, which switches the
+    /// effect on; `None` deletes a character. Space ticks the plain options.
+    pub fn edit(&mut self, c: Option<char>) {
+        let text = match self.selected() {
+            RuleField::Match => &mut self.pattern,
+            RuleField::Float | RuleField::Reload if c == Some(' ') => return self.cycle(true),
+            _ => match self.effect_mut() {
+                Some(effect) => {
+                    effect.enabled = true;
+                    &mut effect.value
+                }
+                None => return,
+            },
+        };
+        match c {
+            Some(c) => text.push(c),
+            None => {
+                text.pop();
+            }
+        }
+    }
+}
+
+// Copy `path` to `<name>.<timestamp>.bak` next to it, adding `-2`, `-3`, ...
+// when a backup from the same second is already there
+fn back_up(path: &Path) -> io::Result<PathBuf> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    for n in 1.. {
+        let suffix = if n == 1 { String::new() } else { format!("-{}", n) };
+        let backup = path.with_file_name(format!("{}.{}{}.bak", name, stamp, suffix));
+        match OpenOptions::new().write(true).create_new(true).open(&backup) {
+            Ok(mut file) => {
+                io::copy(&mut fs::File::open(path)?, &mut file)?;
+                return Ok(backup);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("ran out of backup names")
+}
+
+/// Append `lines` to the config file at `path`, first copying it to
+/// `<name>.<timestamp>.bak` next to it; returns the backup, if there was a
+/// file to back up
+pub fn append_rules(path: &Path, lines: &[String]) -> io::Result<Option<PathBuf>> {
+    let existing = match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    let backup = match &existing {
+        Some(_) => Some(back_up(path)?),
+        None => None,
+    };
+
+    let mut text = String::new();
+    if existing.as_deref().is_some_and(|t| !t.is_empty() && !t.ends_with('\n')) {
+        text.push('\n');
+    }
+    for line in lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(text.as_bytes())?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> Window {
+        Window {
+            monitor: 1,
+            at: (2660, 140),
+            size: (400, 600),
+            initial_class: "gnome-calculator".to_string(),
+            ..Window::new("0x1", "org.gnome.Calculator", "Calculator, basic", 3)
+        }
+    }
+
+    fn monitor() -> Monitor {
+        Monitor {
+            x: 2560,
+            active_workspace_id: 3,
+            active_workspace: "3".to_string(),
+            ..Monitor::new(1, "HDMI-A-1", 1920, 1080)
+        }
+    }
+
+    #[test]
+    fn nothing_enabled_makes_no_lines() {
+        let builder = RuleBuilder::new(&window(), Some(&monitor()), Vec::new(), false);
+        assert!(builder.lines().is_empty());
+    }
+
+    #[test]
+    fn one_line_per_effect_with_an_escaped_match() {
+        let mut builder = RuleBuilder::new(&window(), Some(&monitor()), Vec::new(), false);
+        builder.float = true;
+        builder.size.enabled = true;
+        builder.position.enabled = true;
+        assert_eq!(
+            builder.lines(),
+            [
+                r"windowrule = float, class:^(org\.gnome\.Calculator)$",
+                r"windowrule = size 400 600, class:^(org\.gnome\.Calculator)$",
+                r"windowrule = move 100 140, class:^(org\.gnome\.Calculator)$",
+            ]
+        );
+    }
+
+    #[test]
+    fn switching_the_match_refills_the_pattern() {
+        let mut builder = RuleBuilder::new(&window(), None, Vec::new(), false);
+        builder.cycle(true);
+        assert_eq!(builder.match_by, MatchBy::Title);
+        // The comma would end the rule early
+        assert_eq!(builder.pattern, "^(Calculator. basic)$");
+        builder.cycle(true);
+        assert_eq!(builder.pattern, "^(gnome-calculator)$");
+        builder.cycle(false);
+        builder.cycle(false);
+        assert_eq!(builder.match_by, MatchBy::Class);
+    }
+
+    #[test]
+    fn typing_into_an_effect_enables_it() {
+        let mut builder = RuleBuilder::new(&window(), None, Vec::new(), false);
+        builder.field = RuleField::ALL.iter().position(|&f| f == RuleField::Workspace).unwrap();
+        builder.edit(None);
+        builder.edit(Some('5'));
+        assert_eq!(builder.lines(), [r"windowrule = workspace 5, class:^(org\.gnome\.Calculator)$"]);
+    }
+
+    #[test]
+    fn every_write_gets_its_own_backup() {
+        let dir = crate::scratch_dir("rules");
+        let path = dir.join("hyprland.conf");
+        fs::write(&path, "monitor = , preferred, auto, 1").unwrap();
+
+        let first = append_rules(&path, &["windowrule = float, class:a".to_string()]).unwrap().unwrap();
+        let second = append_rules(&path, &["windowrule = float, class:b".to_string()]).unwrap().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "monitor = , preferred, auto, 1");
+        assert_eq!(
+            fs::read_to_string(&second).unwrap(),
+            "monitor = , preferred, auto, 1\nwindowrule = float, class:a\n"
+        );
+        assert!(fs::read_to_string(&path).unwrap().ends_with("class:a\nwindowrule = float, class:b\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
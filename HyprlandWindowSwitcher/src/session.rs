@@ -0,0 +1,492 @@
+// Session snapshot and restore of the window layout
+//
+// `save` writes every client to a versioned JSON file; `restore` matches the
+// saved entries against the running clients, moves matched windows back and
+// relaunches the missing ones through `hyprctl dispatch exec`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions::quote;
+use crate::backend::{get_monitors, get_windows, get_workspaces, try_dispatch};
+use crate::model::{Monitor, Window, Workspace};
+use crate::state_dir;
+
+// Bump when the file layout changes incompatibly
+const SESSION_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Session {
+    version: u32,
+    // Seconds since the Unix epoch
+    saved_at: u64,
+    windows: Vec<SavedWindow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedWindow {
+    class: String,
+    initial_class: String,
+    title: String,
+    workspace_id: i64,
+    workspace_name: String,
+    monitor: String,
+    at: (i64, i64),
+    size: (i64, i64),
+    floating: bool,
+    pid: i64,
+    // argv of the owning process, empty when it could not be read
+    command: Vec<String>,
+}
+
+impl SavedWindow {
+    // Special workspaces are addressed by their bare `special:…` name, named
+    // ones by `name:` as their id changes between sessions
+    fn workspace_target(&self) -> String {
+        if self.workspace_id < 0 {
+            self.workspace_name.clone()
+        } else if self.workspace_id.to_string() != self.workspace_name {
+            format!("name:{}", self.workspace_name)
+        } else {
+            self.workspace_id.to_string()
+        }
+    }
+}
+
+// One change needed to bring the running session back to the saved one
+enum Step {
+    Keep { saved: SavedWindow },
+    Move { saved: SavedWindow, window: Box<Window>, workspace: bool, floating: bool, geometry: bool },
+    Launch { saved: SavedWindow },
+    MoveWorkspace { workspace: String, monitor: String },
+    Skip { saved: SavedWindow, reason: &'static str },
+}
+
+fn default_path() -> PathBuf {
+    state_dir().join("session.json")
+}
+
+fn read_cmdline(pid: i64) -> Vec<String> {
+    fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|raw| {
+            raw.split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn short_title(title: &str) -> String {
+    const MAX: usize = 40;
+    if title.chars().count() > MAX {
+        format!("{}…", title.chars().take(MAX - 1).collect::<String>())
+    } else {
+        title.to_string()
+    }
+}
+
+pub fn save(path: Option<PathBuf>) -> io::Result<()> {
+    let path = path.unwrap_or_else(default_path);
+    let monitors = get_monitors()?;
+    let workspaces = get_workspaces()?;
+
+    let windows: Vec<SavedWindow> = get_windows()?
+        .into_iter()
+        .map(|win| SavedWindow {
+            workspace_name: workspaces
+                .iter()
+                .find(|ws| ws.id == win.workspace_id)
+                .map(|ws| ws.name.clone())
+                .unwrap_or_else(|| win.workspace.clone()),
+            monitor: monitors
+                .iter()
+                .find(|m| m.id == win.monitor)
+                .map(|m| m.name.clone())
+                .unwrap_or_default(),
+            command: read_cmdline(win.pid),
+            class: win.class,
+            initial_class: win.initial_class,
+            title: win.title,
+            workspace_id: win.workspace_id,
+            at: win.at,
+            size: win.size,
+            floating: win.floating,
+            pid: win.pid,
+        })
+        .collect();
+
+    let session = Session {
+        version: SESSION_VERSION,
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        windows,
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(&session).map_err(io::Error::other)?;
+    fs::write(&path, json)?;
+    println!("Saved {} windows to {}", session.windows.len(), path.display());
+    Ok(())
+}
+
+fn load(path: &Path) -> io::Result<Session> {
+    let raw = fs::read_to_string(path)?;
+    let session: Session = serde_json::from_str(&raw)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if session.version != SESSION_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "unsupported session version {} (expected {})",
+                session.version, SESSION_VERSION
+            ),
+        ));
+    }
+    Ok(session)
+}
+
+/// Pair saved entries with running windows: exact class and title first,
+/// then any remaining window of the same class
+fn match_windows(saved: &[SavedWindow], running: &[Window]) -> Vec<Option<usize>> {
+    let mut taken = vec![false; running.len()];
+    let mut matches = vec![None; saved.len()];
+
+    for exact_title in [true, false] {
+        for (i, entry) in saved.iter().enumerate() {
+            if matches[i].is_some() {
+                continue;
+            }
+            let fits = |w: &Window| w.class == entry.class && (!exact_title || w.title == entry.title);
+            if let Some(j) = (0..running.len()).find(|&j| !taken[j] && fits(&running[j])) {
+                taken[j] = true;
+                matches[i] = Some(j);
+            }
+        }
+    }
+    matches
+}
+
+/// What it takes to get from the running windows, workspaces and monitors
+/// back to `session`
+fn plan(session: &Session, running: &[Window], workspaces: &[Workspace], monitors: &[Monitor]) -> Vec<Step> {
+    let matches = match_windows(&session.windows, running);
+    let mut steps = Vec::new();
+    let mut launched_pids = Vec::new();
+
+    for (entry, matched) in session.windows.iter().zip(matches) {
+        let saved = entry.clone();
+        match matched {
+            Some(j) => {
+                let window = Box::new(running[j].clone());
+                let workspace = window.workspace_id != entry.workspace_id;
+                let floating = window.floating != entry.floating;
+                let geometry = entry.floating && (window.at != entry.at || window.size != entry.size);
+                if workspace || floating || geometry {
+                    steps.push(Step::Move { saved, window, workspace, floating, geometry });
+                } else {
+                    steps.push(Step::Keep { saved });
+                }
+            }
+            // Several windows of one process (browser windows, say) come back from a single launch
+            None if launched_pids.contains(&entry.pid) => {}
+            None if entry.command.is_empty() => {
+                steps.push(Step::Skip { saved, reason: "no launch command recorded" });
+            }
+            None => {
+                launched_pids.push(entry.pid);
+                steps.push(Step::Launch { saved });
+            }
+        }
+    }
+
+    // Put workspaces back on the monitor they were saved on, when that monitor is connected
+    let mut seen = Vec::new();
+    for entry in &session.windows {
+        if seen.contains(&entry.workspace_id) {
+            continue;
+        }
+        seen.push(entry.workspace_id);
+        let current = workspaces.iter().find(|ws| ws.id == entry.workspace_id);
+        let connected = monitors.iter().any(|m| m.name == entry.monitor);
+        if let Some(ws) = current {
+            if connected && ws.monitor != entry.monitor {
+                steps.push(Step::MoveWorkspace {
+                    workspace: ws.dispatch_target(),
+                    monitor: entry.monitor.clone(),
+                });
+            }
+        }
+    }
+
+    steps
+}
+
+fn describe(step: &Step) -> String {
+    match step {
+        Step::Keep { saved } => format!(
+            "  = {} '{}' already on workspace {}",
+            saved.class,
+            short_title(&saved.title),
+            saved.workspace_name
+        ),
+        Step::Move { saved, window, workspace, floating, geometry } => {
+            let mut changes = Vec::new();
+            if *workspace {
+                changes.push(format!("workspace {} → {}", window.workspace, saved.workspace_name));
+            }
+            if *floating {
+                changes.push(if saved.floating { "tiled → floating" } else { "floating → tiled" }.to_string());
+            }
+            if *geometry {
+                changes.push(format!(
+                    "{}x{}+{}+{} → {}x{}+{}+{}",
+                    window.size.0, window.size.1, window.at.0, window.at.1,
+                    saved.size.0, saved.size.1, saved.at.0, saved.at.1
+                ));
+            }
+            format!(
+                "  ~ {} '{}': {}",
+                saved.class,
+                short_title(&saved.title),
+                changes.join(", ")
+            )
+        }
+        Step::Launch { saved } => format!(
+            "  + launch `{}` on workspace {}",
+            saved.command.join(" "),
+            saved.workspace_name
+        ),
+        Step::MoveWorkspace { workspace, monitor } => {
+            format!("  ~ workspace {} → monitor {}", workspace, monitor)
+        }
+        Step::Skip { saved, reason } => format!(
+            "  ! skip {} '{}': {}",
+            saved.class,
+            short_title(&saved.title),
+            reason
+        ),
+    }
+}
+
+// The dispatcher calls that carry out `step`, each a dispatcher and its argument
+fn dispatches(step: &Step) -> Vec<Vec<String>> {
+    let call = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let mut calls = Vec::new();
+    match step {
+        Step::Keep { .. } | Step::Skip { .. } => {}
+        Step::Move { saved, window, workspace, floating, geometry } => {
+            let address = format!("address:{}", window.id);
+            if *workspace {
+                calls.push(call(&[
+                    "movetoworkspacesilent",
+                    &format!("{},{}", saved.workspace_target(), address),
+                ]));
+            }
+            if *floating {
+                calls.push(call(&["togglefloating", &address]));
+            }
+            if *geometry || (*floating && saved.floating) {
+                calls.push(call(&[
+                    "resizewindowpixel",
+                    &format!("exact {} {},{}", saved.size.0, saved.size.1, address),
+                ]));
+                calls.push(call(&[
+                    "movewindowpixel",
+                    &format!("exact {} {},{}", saved.at.0, saved.at.1, address),
+                ]));
+            }
+        }
+        Step::Launch { saved } => {
+            // Window rules on exec place the new window without racing its creation
+            let mut rules = vec![format!("workspace {} silent", saved.workspace_target())];
+            if saved.floating {
+                rules.push("float".to_string());
+                rules.push(format!("size {} {}", saved.size.0, saved.size.1));
+                rules.push(format!("move {} {}", saved.at.0, saved.at.1));
+            }
+            let command: Vec<String> = saved.command.iter().map(|arg| quote(arg)).collect();
+            calls.push(call(&["exec", &format!("[{}] {}", rules.join("; "), command.join(" "))]));
+        }
+        Step::MoveWorkspace { workspace, monitor } => {
+            calls.push(call(&["moveworkspacetomonitor", workspace, monitor]));
+        }
+    }
+    calls
+}
+
+// Run the calls for `step`, stopping at the first one Hyprland rejects
+fn apply(step: &Step) -> io::Result<()> {
+    for args in dispatches(step) {
+        try_dispatch(&args).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", args.join(" "), e)))?;
+    }
+    Ok(())
+}
+
+pub fn restore(path: Option<PathBuf>, dry_run: bool) -> io::Result<()> {
+    let path = path.unwrap_or_else(default_path);
+    let session = load(&path)?;
+    let steps = plan(&session, &get_windows()?, &get_workspaces()?, &get_monitors()?);
+
+    println!(
+        "{} {} saved windows from {}",
+        if dry_run { "Would restore" } else { "Restoring" },
+        session.windows.len(),
+        path.display()
+    );
+    let mut failed = 0;
+    for step in &steps {
+        println!("{}", describe(step));
+        if dry_run {
+            continue;
+        }
+        if let Err(e) = apply(step) {
+            eprintln!("    failed: {}", e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(io::Error::other(format!("{} of {} steps failed", failed, steps.len())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(class: &str, title: &str, workspace_id: i64, pid: i64) -> SavedWindow {
+        SavedWindow {
+            class: class.to_string(),
+            initial_class: class.to_string(),
+            title: title.to_string(),
+            workspace_id,
+            workspace_name: workspace_id.to_string(),
+            monitor: "DP-1".to_string(),
+            at: (0, 0),
+            size: (0, 0),
+            floating: false,
+            pid,
+            command: vec![class.to_string()],
+        }
+    }
+
+    fn session(windows: Vec<SavedWindow>) -> Session {
+        Session {
+            version: SESSION_VERSION,
+            saved_at: 0,
+            windows,
+        }
+    }
+
+    fn workspace(id: i64, monitor: &str) -> Workspace {
+        Workspace {
+            id,
+            name: id.to_string(),
+            monitor: monitor.to_string(),
+            windows: 1,
+        }
+    }
+
+    fn monitor(name: &str) -> Monitor {
+        Monitor {
+            focused: true,
+            ..Monitor::new(0, name, 1920, 1080)
+        }
+    }
+
+    #[test]
+    fn exact_titles_are_matched_before_any_window_of_the_class() {
+        let saved = [saved("kitty", "logs", 2, 1), saved("kitty", "editor", 1, 1)];
+        let running = [Window::new("0x1", "kitty", "editor", 1), Window::new("0x2", "kitty", "logs", 2)];
+        // Matching in order by class alone would swap the two terminals
+        assert_eq!(match_windows(&saved, &running), [Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn leftover_windows_match_by_class_once_each() {
+        let saved = [saved("kitty", "a", 1, 1), saved("kitty", "b", 1, 1), saved("kitty", "c", 1, 1)];
+        let running = [Window::new("0x1", "kitty", "zsh", 1), Window::new("0x2", "kitty", "b", 1)];
+        assert_eq!(match_windows(&saved, &running), [Some(0), Some(1), None]);
+    }
+
+    #[test]
+    fn windows_of_one_process_are_launched_once() {
+        let mut no_command = saved("mpv", "video", 3, 9);
+        no_command.command.clear();
+        let session = session(vec![
+            saved("firefox", "mail", 1, 7),
+            saved("firefox", "docs", 2, 7),
+            saved("firefox", "other profile", 2, 8),
+            no_command,
+        ]);
+        let steps = plan(&session, &[], &[], &[]);
+        let launched: Vec<&str> = steps
+            .iter()
+            .filter_map(|step| match step {
+                Step::Launch { saved } => Some(saved.title.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(launched, ["mail", "other profile"]);
+        assert!(matches!(steps.last(), Some(Step::Skip { saved, .. }) if saved.class == "mpv"));
+    }
+
+    #[test]
+    fn matched_windows_move_only_when_something_differs() {
+        let session = session(vec![saved("kitty", "logs", 2, 1), saved("code", "main.rs", 1, 2)]);
+        let running = [Window::new("0x1", "kitty", "logs", 1), Window::new("0x2", "code", "main.rs", 1)];
+        let steps = plan(&session, &running, &[], &[]);
+        assert!(matches!(
+            &steps[0],
+            Step::Move { window, workspace: true, floating: false, geometry: false, .. } if window.id == "0x1"
+        ));
+        assert!(matches!(&steps[1], Step::Keep { saved } if saved.class == "code"));
+    }
+
+    #[test]
+    fn special_workspaces_are_targeted_by_their_bare_name() {
+        let mut scratch = saved("kitty", "scratch", -98, 1);
+        scratch.workspace_name = "special:magic".to_string();
+        let mut missing = scratch.clone();
+        missing.class = "btop".to_string();
+        missing.command = vec!["btop".to_string()];
+        let session = session(vec![scratch, missing]);
+        let running = [Window::new("0x1", "kitty", "scratch", 1)];
+        let calls: Vec<Vec<String>> = plan(&session, &running, &[], &[]).iter().flat_map(dispatches).collect();
+        assert_eq!(
+            calls,
+            [
+                ["movetoworkspacesilent", "special:magic,address:0x1"],
+                ["exec", "[workspace special:magic silent] 'btop'"],
+            ]
+        );
+    }
+
+    #[test]
+    fn workspaces_return_only_to_connected_monitors() {
+        let mut elsewhere = saved("code", "main.rs", 2, 2);
+        elsewhere.monitor = "HDMI-A-1".to_string();
+        let session = session(vec![saved("kitty", "logs", 1, 1), elsewhere]);
+        let running = [Window::new("0x1", "kitty", "logs", 1), Window::new("0x2", "code", "main.rs", 2)];
+        let workspaces = [workspace(1, "HDMI-A-1"), workspace(2, "DP-1")];
+        // HDMI-A-1 is unplugged, so workspace 2 stays where it is
+        let steps = plan(&session, &running, &workspaces, &[monitor("DP-1")]);
+        let moves: Vec<(&str, &str)> = steps
+            .iter()
+            .filter_map(|step| match step {
+                Step::MoveWorkspace { workspace, monitor } => Some((workspace.as_str(), monitor.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(moves, [("1", "DP-1")]);
+    }
+}
@@ -0,0 +1,446 @@
+// Focus-time analytics
+//
+// `track` follows the event socket and appends one JSON line per focus or
+// title change to focus.log; `stats` folds that log into time per app,
+// workspace, title or configured title pattern.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveTime, TimeZone};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backend::hyprctl;
+use crate::config::Config;
+use crate::events::{self, HyprEvent};
+use crate::{state_dir, text};
+
+// A single focus span never counts for longer than this, so time spent away
+// from the machine (or with the tracker stopped) does not pile onto the last app
+const MAX_SPAN_SECS: i64 = 30 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FocusRecord {
+    // Seconds since the Unix epoch
+    ts: i64,
+    class: String,
+    title: String,
+    workspace: String,
+}
+
+fn log_path() -> PathBuf {
+    state_dir().join("focus.log")
+}
+
+fn now() -> i64 {
+    Local::now().timestamp()
+}
+
+struct Tracker<W> {
+    log: W,
+    workspace: String,
+    // Focused window, normalized with events::window_address: hyprctl
+    // reports it with a 0x prefix, the events without
+    address: String,
+    class: String,
+    last: Option<(String, String, String)>,
+}
+
+impl<W: Write> Tracker<W> {
+    fn record(&mut self, class: &str, title: &str) -> io::Result<()> {
+        let key = (class.to_string(), title.to_string(), self.workspace.clone());
+        if self.last.as_ref() == Some(&key) {
+            return Ok(());
+        }
+        let record = FocusRecord {
+            ts: now(),
+            class: key.0.clone(),
+            title: key.1.clone(),
+            workspace: key.2.clone(),
+        };
+        let line = serde_json::to_string(&record).map_err(io::Error::other)?;
+        writeln!(self.log, "{}", line)?;
+        self.log.flush()?;
+        self.last = Some(key);
+        Ok(())
+    }
+
+    fn handle(&mut self, event: &HyprEvent) -> io::Result<()> {
+        match event.name.as_str() {
+            // activewindow>>CLASS,TITLE — an empty class means nothing has focus
+            "activewindow" => {
+                let fields = event.fields(2);
+                self.class = fields[0].to_string();
+                let class = self.class.clone();
+                self.record(&class, fields.get(1).unwrap_or(&""))?;
+            }
+            "activewindowv2" => self.address = events::window_address(&event.data),
+            // Title changes of the focused window (browser tabs, editor files) start a new span
+            "windowtitlev2" => {
+                let fields = event.fields(2);
+                if events::window_address(fields[0]) == self.address {
+                    let title = fields.get(1).unwrap_or(&"").to_string();
+                    let class = self.class.clone();
+                    self.record(&class, &title)?;
+                }
+            }
+            "workspace" => self.workspace = event.data.clone(),
+            "focusedmon" => {
+                if let Some(name) = event.fields(2).get(1) {
+                    self.workspace = name.to_string();
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// `track`: record activewindow changes until the event socket closes
+pub fn track() -> io::Result<()> {
+    let path = log_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let log = OpenOptions::new().create(true).append(true).open(&path)?;
+
+    let active: Value = hyprctl(&["activewindow", "-j"])
+        .ok()
+        .and_then(|output| serde_json::from_slice(&output).ok())
+        .unwrap_or(Value::Null);
+    let class = active["class"].as_str().unwrap_or("").to_string();
+    let mut tracker = Tracker {
+        log,
+        workspace: active["workspace"]["name"].as_str().unwrap_or("").to_string(),
+        address: events::window_address(active["address"].as_str().unwrap_or("")),
+        class: class.clone(),
+        last: None,
+    };
+    tracker.record(&class, active["title"].as_str().unwrap_or(""))?;
+    eprintln!("Recording focus changes to {}", path.display());
+
+    for event in events::connect()? {
+        tracker.handle(&event?)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Today,
+    Week,
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    App,
+    Workspace,
+    Title,
+    Pattern,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+struct Row {
+    name: String,
+    seconds: i64,
+    share: f64,
+}
+
+impl Period {
+    // Start of the period in local time, as a Unix timestamp
+    fn start(self) -> i64 {
+        let today = Local::now().date_naive();
+        let day = match self {
+            Period::Today => today,
+            Period::Week => today - ChronoDuration::days(today.weekday().num_days_from_monday() as i64),
+            Period::All => return 0,
+        };
+        Local
+            .from_local_datetime(&day.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|t| t.timestamp())
+            .unwrap_or(0)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Period::Today => "today",
+            Period::Week => "this week",
+            Period::All => "all time",
+        }
+    }
+}
+
+fn read_log() -> io::Result<Vec<FocusRecord>> {
+    let file = match File::open(log_path()) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut records: Vec<FocusRecord> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    records.sort_by_key(|r| r.ts);
+    Ok(records)
+}
+
+fn title_patterns(config: &Config) -> Vec<(String, Regex)> {
+    config
+        .stats
+        .patterns
+        .iter()
+        .filter_map(|p| match Regex::new(&p.regex) {
+            Ok(re) => Some((p.name.clone(), re)),
+            Err(e) => {
+                eprintln!("ignoring title pattern {:?}: {}", p.name, e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Time per group between `start` and `end`: each record lasts until the next
+// one, at most MAX_SPAN_SECS
+fn aggregate(records: &[FocusRecord], start: i64, end: i64, group: Group, patterns: &[(String, Regex)]) -> Vec<Row> {
+    let mut totals: HashMap<String, i64> = HashMap::new();
+
+    for (i, record) in records.iter().enumerate() {
+        if record.class.is_empty() {
+            continue;
+        }
+        let next = records.get(i + 1).map(|r| r.ts).unwrap_or(end);
+        let span_end = next.min(record.ts + MAX_SPAN_SECS).min(end);
+        let span_start = record.ts.max(start);
+        if span_end <= span_start {
+            continue;
+        }
+
+        let key = match group {
+            Group::App => record.class.clone(),
+            Group::Workspace => record.workspace.clone(),
+            Group::Title => record.title.clone(),
+            Group::Pattern => patterns
+                .iter()
+                .find(|(_, re)| re.is_match(&record.title))
+                .map(|(name, _)| name.clone())
+                .unwrap_or_else(|| "(other)".to_string()),
+        };
+        *totals.entry(key).or_default() += span_end - span_start;
+    }
+
+    let total: i64 = totals.values().sum();
+    let mut rows: Vec<Row> = totals
+        .into_iter()
+        .map(|(name, seconds)| Row {
+            name,
+            seconds,
+            share: if total > 0 { seconds as f64 * 100.0 / total as f64 } else { 0.0 },
+        })
+        .collect();
+    rows.sort_by(|a, b| b.seconds.cmp(&a.seconds).then(a.name.cmp(&b.name)));
+    rows
+}
+
+fn format_duration(seconds: i64) -> String {
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if h > 0 {
+        format!("{}h {:02}m", h, m)
+    } else if m > 0 {
+        format!("{}m {:02}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_table(rows: &[Row], period: Period, group: Group) {
+    const NAME_WIDTH: usize = 40;
+    const BAR_WIDTH: f64 = 20.0;
+    let heading = match group {
+        Group::App => "App",
+        Group::Workspace => "Workspace",
+        Group::Title => "Title",
+        Group::Pattern => "Pattern",
+    };
+    let total: i64 = rows.iter().map(|r| r.seconds).sum();
+
+    println!("Focus time {} • total {}", period.label(), format_duration(total));
+    println!();
+    println!("  {:<NAME_WIDTH$}  {:>8}  {:>6}", heading, "Time", "Share");
+    for row in rows {
+        let name = text::truncate(&row.name, NAME_WIDTH);
+        // Pad by display width, `{:<}` counts chars and misaligns CJK titles
+        let name = format!("{}{}", name, " ".repeat(NAME_WIDTH - text::display_width(&name)));
+        let bar = "█".repeat((row.share / 100.0 * BAR_WIDTH).round() as usize);
+        println!(
+            "  {}  {:>8}  {:>5.1}%  {}",
+            name,
+            format_duration(row.seconds),
+            row.share,
+            bar
+        );
+    }
+}
+
+/// `stats [--today|--week|--all] [--by app|workspace|title|pattern] [--csv|--json]`
+pub fn report(mut args: impl Iterator<Item = String>) -> io::Result<()> {
+    let mut period = Period::Today;
+    let mut group = Group::App;
+    let mut format = Format::Table;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--today" => period = Period::Today,
+            "--week" => period = Period::Week,
+            "--all" => period = Period::All,
+            "--csv" => format = Format::Csv,
+            "--json" => format = Format::Json,
+            "--by" => {
+                group = match args.next().as_deref() {
+                    Some("app") => Group::App,
+                    Some("workspace") => Group::Workspace,
+                    Some("title") => Group::Title,
+                    Some("pattern") => Group::Pattern,
+                    other => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "--by expects app, workspace, title or pattern, got {:?}",
+                                other.unwrap_or("")
+                            ),
+                        ))
+                    }
+                }
+            }
+            _ => eprintln!("ignoring unknown argument: {}", arg),
+        }
+    }
+
+    let config = Config::load();
+    for warning in &config.warnings {
+        eprintln!("{}", warning);
+    }
+    let patterns = title_patterns(&config);
+    let rows = aggregate(&read_log()?, period.start(), now(), group, &patterns);
+
+    match format {
+        Format::Table => print_table(&rows, period, group),
+        Format::Csv => {
+            println!("name,seconds,share");
+            for row in &rows {
+                println!("{},{},{:.2}", csv_field(&row.name), row.seconds, row.share);
+            }
+        }
+        Format::Json => {
+            let json = serde_json::to_string_pretty(&rows).map_err(io::Error::other)?;
+            println!("{}", json);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ts: i64, class: &str, title: &str) -> FocusRecord {
+        FocusRecord {
+            ts,
+            class: class.to_string(),
+            title: title.to_string(),
+            workspace: "1".to_string(),
+        }
+    }
+
+    fn event(name: &str, data: &str) -> HyprEvent {
+        HyprEvent {
+            name: name.to_string(),
+            data: data.to_string(),
+        }
+    }
+
+    fn seconds(rows: &[Row]) -> Vec<(&str, i64)> {
+        rows.iter().map(|row| (row.name.as_str(), row.seconds)).collect()
+    }
+
+    #[test]
+    fn spans_end_at_the_next_record_the_cap_or_the_end() {
+        let records = [
+            record(1_000, "firefox", "mail"),
+            record(1_300, "kitty", "zsh"),
+            // Away from the machine for hours
+            record(1_400, "code", "main.rs"),
+            record(10_000, "firefox", "mail"),
+        ];
+        let rows = aggregate(&records, 0, 10_100, Group::App, &[]);
+        assert_eq!(seconds(&rows), [("code", MAX_SPAN_SECS), ("firefox", 400), ("kitty", 100)]);
+        let total: f64 = rows.iter().map(|row| row.share).sum();
+        assert!((total - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn spans_are_cut_to_the_period() {
+        let records = [record(1_000, "firefox", "mail"), record(1_600, "kitty", "zsh")];
+        // Starts halfway through firefox, kitty is still focused at the end
+        let rows = aggregate(&records, 1_300, 1_700, Group::App, &[]);
+        assert_eq!(seconds(&rows), [("firefox", 300), ("kitty", 100)]);
+        assert!(aggregate(&records, 2_000, 3_000, Group::Title, &[]).iter().all(|row| row.name == "zsh"));
+    }
+
+    #[test]
+    fn nothing_focused_is_not_counted_and_patterns_group_titles() {
+        let records = [
+            record(0, "firefox", "Pull Request #12"),
+            record(100, "", ""),
+            record(200, "firefox", "Inbox"),
+            record(260, "code", "Merge Request !3"),
+        ];
+        let patterns = [("review".to_string(), Regex::new("Pull Request|Merge Request").unwrap())];
+        let rows = aggregate(&records, 0, 300, Group::Pattern, &patterns);
+        assert_eq!(seconds(&rows), [("review", 140), ("(other)", 60)]);
+    }
+
+    #[test]
+    fn title_changes_match_the_first_window_despite_the_prefix() {
+        // As set up from `hyprctl activewindow`, which prints 0x…
+        let mut tracker = Tracker {
+            log: Vec::new(),
+            workspace: "1".to_string(),
+            address: events::window_address("0x55d1c0"),
+            class: "firefox".to_string(),
+            last: None,
+        };
+        tracker.handle(&event("windowtitlev2", "55d1c0,Inbox (2)")).unwrap();
+        tracker.handle(&event("windowtitlev2", "99aa00,Other window")).unwrap();
+        tracker.handle(&event("activewindowv2", "99aa00")).unwrap();
+        tracker.handle(&event("activewindow", "kitty,zsh")).unwrap();
+        tracker.handle(&event("windowtitlev2", "99aa00,vim")).unwrap();
+        let log = String::from_utf8(tracker.log).unwrap();
+        let titles: Vec<String> = log
+            .lines()
+            .map(|line| serde_json::from_str::<FocusRecord>(line).unwrap().title)
+            .collect();
+        assert_eq!(titles, ["Inbox (2)", "zsh", "vim"]);
+    }
+}
@@ -0,0 +1,239 @@
+// Text layout for cards and labels, measured in terminal cells
+//
+// Window titles can hold anything: CJK (two cells per character), emoji,
+// combining accents. Everything here splits at grapheme boundaries and counts
+// display width rather than bytes or chars, so nothing panics and nothing
+// spills over the card border.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const ELLIPSIS: char = '…';
+
+/// Width of `text` in terminal cells
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+// Longest run of leading graphemes that fits in `width` cells
+fn take_width(text: &str, width: usize) -> (&str, usize) {
+    let mut used = 0;
+    let mut end = 0;
+    for (i, g) in text.grapheme_indices(true) {
+        let w = display_width(g);
+        if used + w > width {
+            break;
+        }
+        used += w;
+        end = i + g.len();
+    }
+    (&text[..end], used)
+}
+
+// Longest run of trailing graphemes that fits in `width` cells
+fn take_width_back(text: &str, width: usize) -> (&str, usize) {
+    let mut used = 0;
+    let mut start = text.len();
+    for (i, g) in text.grapheme_indices(true).rev() {
+        let w = display_width(g);
+        if used + w > width {
+            break;
+        }
+        used += w;
+        start = i;
+    }
+    (&text[start..], used)
+}
+
+/// Cut `text` to `width` cells, marking the cut with an ellipsis
+pub fn truncate(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let (head, _) = take_width(text, width - 1);
+    format!("{}{}", head, ELLIPSIS)
+}
+
+/// Cut or pad `text` to exactly `width` cells, for lining up columns
+pub fn fit(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let padding = width.saturating_sub(display_width(&text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Cut the middle out of `text` so both ends stay visible, which suits paths
+/// and command lines: `/usr/share/…/firefox.desktop`
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let available = width - 1;
+    let (head, head_width) = take_width(text, available / 2);
+    // The end usually carries the file name, it gets whatever the head left
+    let (tail, _) = take_width_back(text, available - head_width);
+    format!("{}{}{}", head, ELLIPSIS, tail)
+}
+
+/// Word-wrap `text` into at most `max_lines` lines of `width` cells. Words
+/// longer than a line are broken between graphemes; text that does not fit
+/// ends in an ellipsis. Always returns at least one (possibly empty) line.
+pub fn wrap_text(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    if width == 0 || max_lines == 0 {
+        return vec![String::new()];
+    }
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    let mut truncated = false;
+
+    'words: for word in text.split_whitespace() {
+        let word_width = display_width(word);
+        let gap = usize::from(!current.is_empty());
+        if current_width + gap + word_width <= width {
+            if gap == 1 {
+                current.push(' ');
+            }
+            current.push_str(word);
+            current_width += gap + word_width;
+            continue;
+        }
+
+        // Start a new line for the word
+        if !current.is_empty() {
+            if lines.len() + 1 == max_lines {
+                truncated = true;
+                break;
+            }
+            lines.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+        if word_width <= width {
+            current.push_str(word);
+            current_width = word_width;
+            continue;
+        }
+
+        // Too long for any line: fill lines grapheme by grapheme
+        for g in word.graphemes(true) {
+            let w = display_width(g);
+            if w > width {
+                // A wide glyph in a one-cell column can never be shown
+                truncated = true;
+                break 'words;
+            }
+            if current_width + w > width {
+                if lines.len() + 1 == max_lines {
+                    truncated = true;
+                    break 'words;
+                }
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            current.push_str(g);
+            current_width += w;
+        }
+    }
+
+    if truncated {
+        let (kept, _) = take_width(&current, width - 1);
+        lines.push(format!("{}{}", kept, ELLIPSIS));
+    } else if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Visible characters only, the way a reader compares two texts
+    fn content(text: &str) -> String {
+        text.split_whitespace().collect()
+    }
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        assert_eq!(wrap_text("one two three", 7, 3), ["one two", "three"]);
+    }
+
+    #[test]
+    fn breaks_long_words_between_graphemes() {
+        assert_eq!(wrap_text("abcdefgh", 3, 3), ["abc", "def", "gh"]);
+        // "é" as e + combining acute stays in one piece
+        assert_eq!(wrap_text("e\u{301}e\u{301}e\u{301}", 2, 2), ["e\u{301}e\u{301}", "e\u{301}"]);
+    }
+
+    #[test]
+    fn counts_wide_glyphs_as_two_cells() {
+        assert_eq!(wrap_text("日本語のタイトル", 6, 3), ["日本語", "のタイ", "トル"]);
+        assert_eq!(wrap_text("🦀🦀🦀", 5, 1), ["🦀🦀…"]);
+        assert_eq!(fit("日本", 5), "日本 ");
+        assert_eq!(fit("日本語", 5), "日本…");
+    }
+
+    #[test]
+    fn ellipsis_marks_dropped_text() {
+        assert_eq!(wrap_text("one two three four", 9, 2), ["one two", "three…"]);
+        assert_eq!(truncate("Mozilla Firefox", 8), "Mozilla…");
+        assert_eq!(truncate_middle("/usr/share/applications/firefox.desktop", 20), "/usr/shar…ox.desktop");
+    }
+
+    proptest! {
+        #[test]
+        fn lines_fit_the_width(text in "\\PC*", width in 1usize..40, max_lines in 1usize..5) {
+            let lines = wrap_text(&text, width, max_lines);
+            prop_assert!(!lines.is_empty());
+            prop_assert!(lines.len() <= max_lines);
+            for line in &lines {
+                prop_assert!(display_width(line) <= width, "{:?} wider than {}", line, width);
+            }
+        }
+
+        #[test]
+        fn wrapping_keeps_text_in_order(text in "\\PC*", width in 1usize..40, max_lines in 1usize..5) {
+            let wrapped = content(&wrap_text(&text, width, max_lines).concat());
+            let kept = wrapped.strip_suffix(ELLIPSIS).unwrap_or(&wrapped);
+            prop_assert!(content(&text).starts_with(kept));
+        }
+
+        #[test]
+        fn short_text_is_untouched(words in prop::collection::vec("[\\PC&&\\S]{1,6}", 0..4)) {
+            let text = words.join(" ");
+            let width = display_width(&text).max(1);
+            let expected = if text.is_empty() { vec![String::new()] } else { vec![text.clone()] };
+            prop_assert_eq!(wrap_text(&text, width, 1), expected);
+        }
+
+        // Without ellipses of its own, so the one marking the cut can be found
+        #[test]
+        fn truncation_fits_and_keeps_the_ends(text in "[\\PC&&[^…]]*", width in 0usize..30) {
+            let end = truncate(&text, width);
+            let middle = truncate_middle(&text, width);
+            prop_assert!(display_width(&end) <= width);
+            prop_assert!(display_width(&middle) <= width);
+            if display_width(&text) <= width {
+                prop_assert_eq!(&end, &text);
+                prop_assert_eq!(&middle, &text);
+            } else if width > 0 {
+                prop_assert!(text.starts_with(end.trim_end_matches(ELLIPSIS)));
+                let (head, tail) = middle.split_once(ELLIPSIS).unwrap();
+                prop_assert!(text.starts_with(head));
+                prop_assert!(text.ends_with(tail));
+            }
+        }
+    }
+}
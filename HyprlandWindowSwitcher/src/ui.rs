@@ -0,0 +1,1013 @@
+// Rendering: everything drawn into a frame, from App state only
+
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::{Line, Span, Text},
+    widgets::{
+        canvas::{Canvas, Rectangle},
+        Block, Borders, BorderType, Clear, Paragraph, Padding, Wrap,
+    },
+};
+
+use crate::app::{ActionState, App, HitTarget, Pager, Prompt, PromptKind, SortColumn, View};
+use crate::launcher::LauncherSource;
+use crate::layout::{
+    split_screen, workspace_slot_label, workspace_slots, ListLayout, MapRect, MinimapLayout, StripLayout, LIST_ICON_WIDTH,
+};
+use crate::model::Window;
+use crate::rules::{RuleBuilder, RuleField};
+use crate::text::{self, wrap_text};
+
+// Theme configuration
+pub struct Theme {
+    background: Color,
+    surface: Color,
+    surface_variant: Color,
+    primary: Color,
+    on_background: Color,
+    on_surface: Color,
+    accent: Color,
+    border_selected: Color,
+    border_normal: Color,
+    error: Color,
+    attention: Color,
+}
+
+impl Theme {
+    // Dracula theme for a more attractive and visible UI
+    pub fn dracula() -> Self {
+        Theme {
+            background: Color::Rgb(40, 42, 54),      // Dark background
+            surface: Color::Rgb(68, 71, 90),         // Lighter background for UI elements
+            surface_variant: Color::Rgb(98, 114, 164), // A lighter shade for selected items
+            primary: Color::Rgb(189, 147, 249),      // Vibrant purple for primary accents
+            on_background: Color::Rgb(248, 248, 242),  // Bright foreground for text
+            on_surface: Color::Rgb(248, 248, 242),     // Bright foreground for text on surfaces
+            accent: Color::Rgb(80, 250, 123),        // Bright green for secondary accents
+            border_selected: Color::Rgb(255, 121, 198),// Striking pink for selected borders
+            border_normal: Color::Rgb(98, 114, 164),   // Subdued border color
+            error: Color::Rgb(255, 85, 85),          // Red for close/error actions
+            attention: Color::Rgb(255, 184, 108),    // Orange for windows asking for attention
+        }
+    }
+}
+
+fn render_header(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    let header_text = Text::from(vec![
+        Line::from(vec![
+            Span::styled("󰖲 ", Style::default().fg(app.theme.accent)),
+            Span::styled(
+                "Hyprland Window Switcher",
+                Style::default()
+                    .fg(app.theme.on_background)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        if app.filtering || !app.filter_text.is_empty() {
+            Line::from(vec![
+                Span::styled("󰍉 Filter: ", Style::default().fg(app.theme.accent)),
+                Span::styled(
+                    format!("{}{}", app.filter_text, if app.filtering { "▏" } else { "" }),
+                    Style::default().fg(app.theme.on_background).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(
+                        "  {} windows, {} to launch • #tag: by tag • Enter: focus/launch • Esc: clear",
+                        app.visible.len(),
+                        app.launch_items.len()
+                    ),
+                    Style::default().fg(app.theme.on_surface).add_modifier(Modifier::DIM),
+                ),
+            ])
+        } else {
+            Line::from(vec![Span::styled(
+                key_hints(app).join(" • "),
+                Style::default().fg(app.theme.on_surface).add_modifier(Modifier::DIM),
+            )])
+        },
+    ]);
+
+    let header_block = Block::default()
+        .padding(Padding::horizontal(2))
+        .style(Style::default().bg(app.theme.background));
+
+    // Wrap so the key hints spill onto the spare header line on narrow terminals
+    let paragraph = Paragraph::new(header_text)
+        .block(header_block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(paragraph, area);
+}
+
+fn render_workspace_strip(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border_normal))
+        .title(" Drag a card onto a workspace to move it ")
+        .style(Style::default().bg(app.theme.background));
+    frame.render_widget(block, area);
+
+    let dragged_workspace = app
+        .drag
+        .as_ref()
+        .filter(|d| d.moved)
+        .and_then(|d| app.shown(d.source))
+        .map(|w| w.workspace_id);
+    let drop_target = app.drag.as_ref().and_then(|d| d.over);
+
+    let StripLayout { labels, slots } = workspace_slots(app, area);
+    for (rect, label) in labels {
+        let text = Span::styled(label, Style::default().fg(app.theme.primary));
+        frame.render_widget(Paragraph::new(text), rect);
+    }
+    for (rect, i) in slots {
+        let ws = &app.workspaces[i];
+        let style = if drop_target == Some(HitTarget::Workspace(i)) {
+            Style::default()
+                .fg(app.theme.background)
+                .bg(app.theme.accent)
+                .add_modifier(Modifier::BOLD)
+        } else if dragged_workspace == Some(ws.id) {
+            Style::default().fg(app.theme.on_surface).bg(app.theme.surface_variant)
+        } else {
+            Style::default().fg(app.theme.on_surface).bg(app.theme.surface)
+        };
+        frame.render_widget(Paragraph::new(workspace_slot_label(ws)).style(style), rect);
+    }
+}
+
+// Content of one card in the grid; windows and workspaces are both drawn as cards
+struct Card {
+    icon: String,
+    // Hint shown next to the icon while the card is selected
+    hint: &'static str,
+    hint_color: Color,
+    heading: String,
+    body: String,
+    footer: String,
+    // Not-yet-running app: drawn dimmed with a rounded border
+    ghost: bool,
+    // Action progress, urgency or unread note, drawn in its color along with the border
+    badge: Option<(String, Color)>,
+    // Hyprland tags, drawn as chips under the footer
+    tags: Vec<String>,
+    // Picked with Space for a custom action
+    picked: bool,
+}
+
+fn render_cards(
+    frame: &mut ratatui::Frame,
+    area: Rect,
+    app: &App,
+    cards: &[Card],
+    selected: usize,
+    drop_target: Option<usize>,
+) {
+    let grid = app.grid_layout(cards.len(), area);
+    let text_width = grid.text_width;
+
+    for (i, (card, &rect)) in cards.iter().zip(&grid.rects).enumerate() {
+        if rect.height == 0 {
+            continue;
+        }
+        let is_selected = selected == i;
+        let (bg_color, border_color, border_type) = if drop_target == Some(i) {
+            (app.theme.surface, app.theme.accent, BorderType::Double)
+        } else if is_selected {
+            (app.theme.surface_variant, app.theme.border_selected, BorderType::Thick)
+        } else if card.ghost {
+            (app.theme.background, app.theme.border_normal, BorderType::Rounded)
+        } else if let Some((_, color)) = &card.badge {
+            (app.theme.surface, *color, BorderType::Plain)
+        } else {
+            (app.theme.surface, app.theme.border_normal, BorderType::Plain)
+        };
+
+        let mut block_style = Style::default().bg(bg_color);
+        if card.ghost && !is_selected {
+            block_style = block_style.add_modifier(Modifier::DIM);
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(Style::default().fg(border_color))
+            .style(block_style)
+            .padding(Padding::horizontal(1));
+
+        // Calculate dynamic widths based on available space
+        let heading_width = text_width.saturating_sub(2); // Leave some margin
+        let body_width = text_width;
+
+        // Wrap heading and body with dynamic width
+        let wrapped_heading: Vec<Line> = wrap_text(&card.heading, heading_width, 2)
+            .into_iter()
+            .map(|line| {
+                Line::from(Span::styled(
+                    line,
+                    Style::default()
+                        .fg(if is_selected { app.theme.on_background } else { app.theme.on_surface })
+                        .add_modifier(Modifier::BOLD),
+                ))
+            })
+            .collect();
+
+        // Ghost cards show a command line, where the end matters as much as the start
+        let body_lines = if card.ghost {
+            vec![text::truncate_middle(&card.body, body_width)]
+        } else {
+            wrap_text(&card.body, body_width, 2)
+        };
+        let wrapped_body: Vec<Line> = body_lines
+            .into_iter()
+            .map(|line| {
+                Line::from(Span::styled(
+                    line,
+                    Style::default().fg(app.theme.on_surface),
+                ))
+            })
+            .collect();
+
+        let mut lines = Vec::new();
+        // First line: icon and action hint
+        let mut first_line = vec![Span::styled(
+            format!("{} ", card.icon),
+            Style::default().fg(app.theme.primary),
+        )];
+        if card.picked {
+            first_line.push(Span::styled("󰄲 ", Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD)));
+        }
+        if let Some((badge, color)) = &card.badge {
+            // Error messages can be long, the icon keeps its line
+            first_line.push(Span::styled(
+                format!("{} ", text::truncate(badge, text_width.saturating_sub(3))),
+                Style::default().fg(*color).add_modifier(Modifier::BOLD),
+            ));
+        }
+        // A badge takes the place of the hint, both rarely fit on one line
+        if is_selected && card.badge.is_none() {
+            first_line.push(Span::styled(
+                card.hint,
+                Style::default().fg(card.hint_color).add_modifier(Modifier::DIM),
+            ));
+        }
+        lines.push(Line::from(first_line));
+        lines.extend(wrapped_heading);
+        lines.extend(wrapped_body);
+        lines.push(Line::from(Span::styled(
+            card.footer.clone(),
+            Style::default().fg(app.theme.accent).add_modifier(Modifier::DIM),
+        )));
+        if !card.tags.is_empty() {
+            let chip = Style::default().fg(app.theme.background).bg(app.theme.primary);
+            let mut chips = Vec::new();
+            for tag in &card.tags {
+                chips.push(Span::styled(format!("󰓹 {} ", tag), chip));
+                chips.push(Span::raw(" "));
+            }
+            lines.push(Line::from(chips));
+        }
+
+        let paragraph = Paragraph::new(Text::from(lines))
+            .block(block)
+            .alignment(Alignment::Left)
+            .wrap(Wrap { trim: true });
+
+        frame.render_widget(paragraph, rect);
+    }
+}
+
+// The latest action on a window while it runs or just ended, else why the
+// window wants attention
+fn window_badge(app: &App, win: &Window) -> Option<(String, Color)> {
+    match app.actions.get(&win.id) {
+        Some(ActionState::Pending(verb)) => Some((format!("󰔟 {}…", verb.pending), app.theme.primary)),
+        Some(ActionState::Done(verb)) => Some((format!("󰄬 {}", verb.done), app.theme.accent)),
+        Some(ActionState::Failed(error)) => Some((format!("󰅙 {}", error), app.theme.error)),
+        None => app.attention_badge(win).map(|badge| (badge, app.theme.attention)),
+    }
+}
+
+fn render_windows(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    if app.visible.is_empty() && app.launch_items.is_empty() {
+        let message = Paragraph::new("No windows match the current filters • o/w: toggle filters")
+            .style(Style::default().fg(app.theme.on_surface).add_modifier(Modifier::DIM))
+            .alignment(Alignment::Center);
+        frame.render_widget(message, area);
+        return;
+    }
+
+    let mut cards: Vec<Card> = app
+        .visible
+        .iter()
+        .map(|&i| &app.windows[i])
+        .map(|win| Card {
+            icon: app.get_app_icon(&win.class).to_string(),
+            hint: "󰅖 Del/x to close",
+            hint_color: app.theme.error,
+            heading: app.rewriter.class_name(&win.class).to_string(),
+            body: app.rewriter.title(&win.class, &win.title),
+            footer: {
+                let mut footer = format!("󰋁 {}", win.workspace);
+                let keys = app.marks.keys_for(win, &app.windows);
+                if !keys.is_empty() {
+                    footer.push_str(&format!("  󰃀 {}", keys));
+                }
+                if win.fullscreen {
+                    footer.push_str("  󰊓 fullscreen");
+                }
+                if win.hidden {
+                    footer.push_str("  󰘓 hidden");
+                }
+                footer
+            },
+            ghost: false,
+            badge: window_badge(app, win),
+            tags: win.tags.clone(),
+            picked: app.picked.contains(&win.id),
+        })
+        .collect();
+
+    cards.extend(app.launch_items.iter().map(|launcher| Card {
+        icon: app.get_app_icon(&launcher.class).to_string(),
+        hint: "󰐕 Enter to launch",
+        hint_color: app.theme.accent,
+        heading: launcher.name.clone(),
+        body: launcher.command.clone(),
+        footer: match launcher.source {
+            LauncherSource::Favourite => "󰓎 favourite • not running".to_string(),
+            LauncherSource::DesktopEntry => "󰀻 application".to_string(),
+        },
+        ghost: true,
+        badge: None,
+        tags: Vec::new(),
+        picked: false,
+    }));
+
+    let drop_target = app.drag.as_ref().and_then(|d| match d.over {
+        Some(HitTarget::Card(i)) if d.moved && d.source != i => Some(i),
+        _ => None,
+    });
+    render_cards(frame, area, app, &cards, app.selected_index, drop_target);
+}
+
+fn render_workspaces(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    let cards: Vec<Card> = app
+        .workspaces
+        .iter()
+        .map(|ws| {
+            // Summarise the apps on the workspace, collapsing duplicates
+            let mut apps: Vec<(String, usize)> = Vec::new();
+            for win in app.windows.iter().filter(|w| w.workspace_id == ws.id) {
+                match apps.iter_mut().find(|(class, _)| *class == win.class) {
+                    Some((_, count)) => *count += 1,
+                    None => apps.push((win.class.clone(), 1)),
+                }
+            }
+            let body = if apps.is_empty() {
+                "Empty".to_string()
+            } else {
+                apps.iter()
+                    .map(|(class, count)| {
+                        let icon = app.get_app_icon(class);
+                        let name = app.rewriter.class_name(class);
+                        if *count > 1 {
+                            format!("{} {} ×{}", icon, name, count)
+                        } else {
+                            format!("{} {}", icon, name)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            let active = if app.active_workspace == Some(ws.id) {
+                " (active)"
+            } else {
+                ""
+            };
+            let attention = app
+                .windows
+                .iter()
+                .filter(|w| w.workspace_id == ws.id && app.needs_attention(w))
+                .count();
+
+            Card {
+                icon: "󰋁".to_string(),
+                hint: "Enter: switch",
+                hint_color: app.theme.accent,
+                heading: format!("Workspace {}{}", ws.name, active),
+                body,
+                footer: format!("󰍹 {} • {} windows", ws.monitor, ws.windows),
+                ghost: false,
+                badge: (attention > 0).then(|| (format!("󰂚 {}", attention), app.theme.attention)),
+                tags: Vec::new(),
+                picked: false,
+            }
+        })
+        .collect();
+
+    render_cards(frame, area, app, &cards, app.workspace_index, None);
+}
+
+fn render_monitors(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    let cards: Vec<Card> = app
+        .monitors
+        .iter()
+        .map(|monitor| {
+            let total = app.windows.iter().filter(|w| w.monitor == monitor.id).count();
+            // List what is currently on screen: the windows of the active workspace
+            let on_screen: Vec<String> = app
+                .windows
+                .iter()
+                .filter(|w| w.monitor == monitor.id && w.workspace_id == monitor.active_workspace_id)
+                .map(|w| format!("{} {}", app.get_app_icon(&w.class), app.rewriter.class_name(&w.class)))
+                .collect();
+            let body = if on_screen.is_empty() {
+                "No windows on screen".to_string()
+            } else {
+                on_screen.join("  ")
+            };
+            let focused = if monitor.focused { " (focused)" } else { "" };
+
+            Card {
+                icon: "󰍹".to_string(),
+                hint: "Enter: focus",
+                hint_color: app.theme.accent,
+                heading: format!("{}{}", monitor.name, focused),
+                body,
+                footer: format!(
+                    "{}x{}@{:.0}Hz • ×{} • 󰋁 {} • {} windows",
+                    monitor.width,
+                    monitor.height,
+                    monitor.refresh_rate,
+                    monitor.scale,
+                    monitor.active_workspace,
+                    total
+                ),
+                ghost: false,
+                badge: None,
+                tags: Vec::new(),
+                picked: false,
+            }
+        })
+        .collect();
+
+    render_cards(frame, area, app, &cards, app.monitor_index, None);
+}
+
+fn render_prompt(frame: &mut ratatui::Frame, area: Rect, app: &App, prompt: &Prompt) {
+    let (title, text) = match &prompt.kind {
+        PromptKind::RenameWorkspace(_) => (" Rename workspace ".to_string(), format!("{}▏", prompt.input)),
+        PromptKind::CreateWorkspace => (
+            " New workspace name (empty for next free number) ".to_string(),
+            format!("{}▏", prompt.input),
+        ),
+        PromptKind::Tags(address) => {
+            let tags = app
+                .windows
+                .iter()
+                .find(|w| &w.id == address)
+                .map(|w| w.tags.join(" "))
+                .filter(|tags| !tags.is_empty())
+                .unwrap_or_else(|| "none".to_string());
+            (
+                format!(" Toggle tags (+tag adds, -tag removes) • now: {} ", tags),
+                format!("{}▏", prompt.input),
+            )
+        }
+        PromptKind::CloseWorkspaceWindows(i) => {
+            let (name, count) = app
+                .workspaces
+                .get(*i)
+                .map(|ws| (ws.name.as_str(), ws.windows))
+                .unwrap_or(("?", 0));
+            (
+                " Close workspace windows ".to_string(),
+                format!("Close all {} windows on workspace {}? (y/n)", count, name),
+            )
+        }
+    };
+
+    let width = area.width.min(60);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + area.height.saturating_sub(3) / 2,
+        width,
+        area.height.min(3),
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(app.theme.border_selected))
+        .title(title)
+        .style(Style::default().bg(app.theme.surface));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(text)
+            .style(Style::default().fg(app.theme.on_surface))
+            .block(block),
+        popup,
+    );
+}
+
+// `i`: everything known about the selected window, including the raw title
+fn render_details(frame: &mut ratatui::Frame, area: Rect, app: &App, win: &Window) {
+    let flag = |on: bool| if on { "yes" } else { "no" };
+    let fields = [
+        ("Class", win.class.clone()),
+        ("Shown as", app.rewriter.class_name(&win.class).to_string()),
+        ("Initial class", win.initial_class.clone()),
+        ("Title", win.title.clone()),
+        ("Shown title", app.rewriter.title(&win.class, &win.title)),
+        ("Address", win.id.clone()),
+        ("PID", win.pid.to_string()),
+        ("Workspace", win.workspace.clone()),
+        ("Position", format!("{},{}", win.at.0, win.at.1)),
+        ("Size", format!("{}x{}", win.size.0, win.size.1)),
+        ("Floating", flag(win.floating).to_string()),
+        ("Fullscreen", flag(win.fullscreen).to_string()),
+        ("Pinned", flag(win.pinned).to_string()),
+        ("Tags", win.tags.join(", ")),
+    ];
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(format!("{:<14}", label), Style::default().fg(app.theme.accent)),
+                Span::styled(value, Style::default().fg(app.theme.on_surface)),
+            ])
+        })
+        .collect();
+
+    let width = area.width.min(80);
+    let height = area.height.min(lines.len() as u16 + 2);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(app.theme.border_selected))
+        .title(" Window details (any key to close) ")
+        .style(Style::default().bg(app.theme.surface));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        popup,
+    );
+}
+
+// Help for the custom actions from the config, like " • S: ssh here"
+// Count of what the view shows, then its keys
+fn key_hints(app: &App) -> Vec<String> {
+    let (found, keys): (String, &[&str]) = match app.view {
+        View::Workspaces => (
+            format!("Found {} workspaces", app.workspaces.len()),
+            &["Enter/double-click: switch", "e: rename", "n: new", "M: next monitor", "Del/x: close windows", "Tab: view"],
+        ),
+        View::Monitors => (
+            format!("Found {} monitors", app.monitors.len()),
+            &["Enter/double-click: focus monitor", "Tab: view"],
+        ),
+        View::List => (
+            format!("Found {} windows{}", app.visible.len(), app.filter_description()),
+            &["↑↓/wheel: move", "click a header/s: sort", "v: cards", "Tab: view"],
+        ),
+        View::Grid | View::Minimap => (
+            format!("Found {} windows{}", app.visible.len(), app.filter_description()),
+            &["←→↑↓/wheel: move", "Tab: view", "v: list"],
+        ),
+    };
+    let mut hints = vec![found];
+    hints.extend(keys.iter().map(|key| key.to_string()));
+    if matches!(app.view, View::List | View::Grid | View::Minimap) {
+        hints.extend(
+            [
+                "/: filter",
+                "Enter/double-click: focus",
+                "Del/x/middle-click: close",
+                "drag: move/swap",
+                "m/': mark/jump",
+                "u: urgent",
+                "i: details",
+                "t: tags",
+                "R: rule",
+                "Space: pick",
+            ]
+            .map(str::to_string),
+        );
+        hints.extend(app.custom_actions.iter().map(|action| format!("{}: {}", action.key, action.name)));
+        hints.extend(["o/w: this monitor/workspace", "p: peek", "r: refresh"].map(str::to_string));
+    }
+    hints.push("q/Esc: quit".to_string());
+    hints
+}
+
+// Result of the last custom action, on the bottom line until the next key
+fn render_toast(frame: &mut ratatui::Frame, area: Rect, app: &App, toast: &Result<String, String>) {
+    if area.height == 0 {
+        return;
+    }
+    let (text, color) = match toast {
+        Ok(text) => (format!(" 󰄬 {}", text), app.theme.accent),
+        Err(text) => (format!(" 󰅚 {}", text), app.theme.error),
+    };
+    let line = Rect::new(area.x, area.y + area.height - 1, area.width, 1);
+    frame.render_widget(Clear, line);
+    frame.render_widget(
+        Paragraph::new(text::fit(&text, line.width as usize))
+            .style(Style::default().bg(app.theme.surface).fg(color)),
+        line,
+    );
+}
+
+// Output of a custom action with `output = "pager"`
+fn render_pager(frame: &mut ratatui::Frame, area: Rect, app: &App, pager: &Pager) {
+    let width = area.width.min(100);
+    let height = area.height.saturating_sub(2).max(area.height.min(3));
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let lines: Vec<Line> = pager
+        .lines
+        .iter()
+        .skip(pager.scroll)
+        .map(|line| {
+            let style = if line.starts_with("── ") {
+                Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.on_surface)
+            };
+            Line::from(Span::styled(line.clone(), style))
+        })
+        .collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(app.theme.border_selected))
+        .title(format!(" {} • ↑↓/PgUp/PgDn scroll • Esc close ", pager.title))
+        .style(Style::default().bg(app.theme.surface));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+// `R`: the window rule builder with a preview of the lines it will append
+fn render_rule_builder(frame: &mut ratatui::Frame, area: Rect, app: &App, builder: &RuleBuilder) {
+    let width = area.width.min(90);
+    let value_width = (width as usize).saturating_sub(20);
+    let mut lines: Vec<Line> = RuleField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &field)| {
+            let check = |on: bool| if on { "[x]" } else { "[ ]" };
+            let (state, value) = match field {
+                RuleField::Match => ("   ", format!("{}: {}", builder.match_by.key(), builder.pattern)),
+                RuleField::Float => (check(builder.float), String::new()),
+                RuleField::Size => (check(builder.size.enabled), builder.size.value.clone()),
+                RuleField::Move => (check(builder.position.enabled), builder.position.value.clone()),
+                RuleField::Workspace => (check(builder.workspace.enabled), builder.workspace.value.clone()),
+                RuleField::Opacity => (check(builder.opacity.enabled), builder.opacity.value.clone()),
+                RuleField::File => (
+                    "   ",
+                    builder.file().map_or("no config file".to_string(), |p| p.display().to_string()),
+                ),
+                RuleField::Reload => (check(builder.reload), "hyprctl reload once written".to_string()),
+            };
+            let selected = i == builder.field;
+            let value = match field {
+                RuleField::File => text::truncate_middle(&value, value_width),
+                _ => text::truncate(&value, value_width),
+            };
+            let label_style = if selected {
+                Style::default().fg(app.theme.border_selected).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.accent)
+            };
+            Line::from(vec![
+                Span::styled(if selected { "▌" } else { " " }, Style::default().fg(app.theme.border_selected)),
+                Span::styled(format!("{} ", state), Style::default().fg(app.theme.primary)),
+                Span::styled(format!("{:<11}", field.label()), label_style),
+                Span::styled(value, Style::default().fg(app.theme.on_surface)),
+            ])
+        })
+        .collect();
+
+    lines.push(Line::default());
+    let preview = builder.lines();
+    if preview.is_empty() {
+        lines.push(Line::styled(
+            " Switch on an effect to see the rule",
+            Style::default().fg(app.theme.on_surface).add_modifier(Modifier::DIM),
+        ));
+    }
+    for line in preview {
+        lines.push(Line::styled(
+            format!(" {}", text::truncate(&line, (width as usize).saturating_sub(3))),
+            Style::default().fg(app.theme.on_background).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let height = area.height.min(lines.len() as u16 + 2);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(app.theme.border_selected))
+        .title(" Window rule • ↑↓ field • ←→ on/off or change • type to edit • Enter: append • Esc ")
+        .style(Style::default().bg(app.theme.surface));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+fn render_minimap(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    let map = MinimapLayout::new(app);
+    // Canvas y grows upwards while Hyprland's grows downwards
+    let flip = |rect: &MapRect| map.height - rect.y - rect.height;
+
+    let canvas = Canvas::default()
+        .background_color(app.theme.background)
+        .marker(Marker::Braille)
+        .x_bounds([0.0, map.width])
+        .y_bounds([0.0, map.height])
+        .paint(|ctx| {
+            for (rect, label) in &map.panels {
+                ctx.draw(&Rectangle {
+                    x: rect.x,
+                    y: flip(rect),
+                    width: rect.width,
+                    height: rect.height,
+                    color: app.theme.border_normal,
+                });
+                ctx.print(
+                    rect.x,
+                    flip(rect) + rect.height,
+                    Span::styled(format!("󰋁 {}", label), Style::default().fg(app.theme.accent)),
+                );
+            }
+            ctx.layer();
+
+            // Draw the selection last so its outline stays on top
+            let mut order: Vec<&(usize, MapRect)> = map.windows.iter().collect();
+            order.sort_by_key(|(i, _)| *i == app.selected_index);
+            for (i, rect) in order {
+                let win = &app.windows[app.visible[*i]];
+                let is_selected = *i == app.selected_index;
+                let color = if is_selected {
+                    app.theme.border_selected
+                } else if win.floating {
+                    app.theme.accent
+                } else {
+                    app.theme.primary
+                };
+                ctx.draw(&Rectangle {
+                    x: rect.x,
+                    y: flip(rect),
+                    width: rect.width,
+                    height: rect.height,
+                    color,
+                });
+                let (cx, _) = rect.center();
+                let mut style = Style::default().fg(if is_selected {
+                    app.theme.on_background
+                } else {
+                    app.theme.on_surface
+                });
+                if is_selected {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                let cells_per_unit = area.width.max(1) as f64 / map.width;
+                let label = text::truncate(
+                    &format!("{} {}", app.get_app_icon(&win.class), app.rewriter.class_name(&win.class)),
+                    (rect.width * cells_per_unit) as usize,
+                );
+                let label_x = (cx - text::display_width(&label) as f64 / 2.0 / cells_per_unit).max(rect.x);
+                ctx.print(label_x, flip(rect) + rect.height / 2.0, Span::styled(label, style));
+            }
+        });
+
+    frame.render_widget(canvas, area);
+}
+
+// One list row: icon, then the text and color of each column
+struct ListRow {
+    icon: String,
+    class: String,
+    title: String,
+    workspace: String,
+    state: String,
+    state_color: Color,
+    ghost: bool,
+    picked: bool,
+}
+
+impl ListRow {
+    fn cell(&self, column: SortColumn) -> &str {
+        match column {
+            SortColumn::Class => &self.class,
+            SortColumn::Title => &self.title,
+            SortColumn::Workspace => &self.workspace,
+            SortColumn::State => &self.state,
+        }
+    }
+}
+
+// Windows then launchers, in list order
+fn list_rows(app: &App) -> Vec<ListRow> {
+    let windows = app.visible.iter().map(|&i| &app.windows[i]).map(|win| {
+        let mut flags = Vec::new();
+        if win.floating {
+            flags.push("󰏌".to_string());
+        }
+        if win.pinned {
+            flags.push("󰐃".to_string());
+        }
+        if win.fullscreen {
+            flags.push("󰊓".to_string());
+        }
+        if win.hidden {
+            flags.push("󰘓".to_string());
+        }
+        let keys = app.marks.keys_for(win, &app.windows);
+        if !keys.is_empty() {
+            flags.push(format!("󰃀 {}", keys));
+        }
+        flags.extend(win.tags.iter().map(|tag| format!("#{}", tag)));
+        // Actions and attention read best in their own color after the flags
+        let badge = window_badge(app, win);
+        let state_color = badge.as_ref().map_or(app.theme.on_surface, |(_, color)| *color);
+        flags.extend(badge.map(|(text, _)| text));
+        ListRow {
+            icon: app.get_app_icon(&win.class).to_string(),
+            class: app.rewriter.class_name(&win.class).to_string(),
+            title: app.rewriter.title(&win.class, &win.title),
+            workspace: format!("󰋁 {}", win.workspace),
+            state: flags.join(" "),
+            state_color,
+            ghost: false,
+            picked: app.picked.contains(&win.id),
+        }
+    });
+    let launchers = app.launch_items.iter().map(|launcher| ListRow {
+        icon: app.get_app_icon(&launcher.class).to_string(),
+        class: launcher.name.clone(),
+        title: text::truncate_middle(&launcher.command, 80),
+        workspace: match launcher.source {
+            LauncherSource::Favourite => "󰓎 favourite".to_string(),
+            LauncherSource::DesktopEntry => "󰀻 application".to_string(),
+        },
+        state: "󰐕 launch".to_string(),
+        state_color: app.theme.accent,
+        ghost: true,
+        picked: false,
+    });
+    windows.chain(launchers).collect()
+}
+
+fn render_list_rows(frame: &mut ratatui::Frame, app: &App, layout: &ListLayout, drop_target: Option<usize>) {
+    let rows = list_rows(app);
+    for &(i, rect) in &layout.rows {
+        let Some(row) = rows.get(i) else {
+            continue;
+        };
+        let selected = i == app.selected_index;
+        let mut base = Style::default();
+        if drop_target == Some(i) {
+            base = base.bg(app.theme.surface);
+        } else if selected {
+            base = base.bg(app.theme.surface_variant);
+        } else if row.ghost {
+            base = base.add_modifier(Modifier::DIM);
+        }
+        let (marker, marker_color) = if drop_target == Some(i) {
+            ("▌", app.theme.accent)
+        } else if row.picked {
+            ("󰄲", app.theme.accent)
+        } else if selected {
+            ("▌", app.theme.border_selected)
+        } else {
+            (" ", app.theme.border_selected)
+        };
+        let mut spans = vec![
+            Span::styled(marker, base.fg(marker_color)),
+            Span::styled(format!("{} ", row.icon), base.fg(app.theme.primary)),
+        ];
+        for &(column, cell) in &layout.columns {
+            let style = match column {
+                SortColumn::Class => base.fg(app.theme.on_background).add_modifier(Modifier::BOLD),
+                SortColumn::Title => base.fg(app.theme.on_surface),
+                SortColumn::Workspace => base.fg(app.theme.accent),
+                SortColumn::State => base.fg(row.state_color),
+            };
+            spans.push(Span::styled(text::fit(row.cell(column), cell.width as usize), style));
+            spans.push(Span::styled(" ", base));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)).style(base), rect);
+    }
+}
+
+// The list view: sortable column headers over one line per window
+fn render_list(frame: &mut ratatui::Frame, area: Rect, app: &App) {
+    if app.item_count() == 0 {
+        render_windows(frame, area, app);
+        return;
+    }
+    let layout = ListLayout::new(app, area);
+    if let Some(header) = layout.header {
+        let style = Style::default().bg(app.theme.surface).fg(app.theme.on_surface);
+        let mut spans = vec![Span::styled(" ".repeat(LIST_ICON_WIDTH as usize), style)];
+        for &(column, cell) in &layout.columns {
+            let (heading, style) = match app.sort {
+                Some(sort) if sort.column == column => (
+                    format!("{} {}", column.heading(), if sort.descending { "▼" } else { "▲" }),
+                    style.fg(app.theme.accent).add_modifier(Modifier::BOLD),
+                ),
+                _ => (column.heading().to_string(), style),
+            };
+            spans.push(Span::styled(text::fit(&heading, cell.width as usize), style));
+            spans.push(Span::styled(" ", style));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)).style(style), header);
+    }
+    let drop_target = app.drag.as_ref().and_then(|d| match d.over {
+        Some(HitTarget::Card(i)) if d.moved && d.source != i => Some(i),
+        _ => None,
+    });
+    render_list_rows(frame, app, &layout, drop_target);
+}
+
+// Inline mode: a prompt line over the list rows, drawn over the terminal's
+// own background so the list sits in the scrollback like fzf
+fn render_inline(frame: &mut ratatui::Frame, app: &App) {
+    let area = frame.size();
+    if area.height == 0 {
+        return;
+    }
+    let dim = Style::default().fg(app.theme.on_surface).add_modifier(Modifier::DIM);
+    let count = format!(" {}/{}", app.item_count(), app.windows.len() + app.launch_items.len());
+    let prompt = Line::from(vec![
+        Span::styled("󰖲 > ", Style::default().fg(app.theme.accent)),
+        Span::styled(
+            format!("{}▏", app.filter_text),
+            Style::default().fg(app.theme.on_background).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(count, dim),
+    ]);
+    frame.render_widget(Paragraph::new(prompt), Rect { height: 1, ..area });
+
+    let rows = Rect { y: area.y + 1, height: area.height - 1, ..area };
+    if app.item_count() == 0 {
+        let message = Paragraph::new("  No windows match").style(dim);
+        frame.render_widget(message, Rect { height: rows.height.min(1), ..rows });
+        return;
+    }
+    render_list_rows(frame, app, &ListLayout::without_header(app, rows), None);
+}
+
+pub fn draw(frame: &mut ratatui::Frame, app: &App) {
+    let size = frame.size();
+    if app.inline {
+        render_inline(frame, app);
+        return;
+    }
+    let bg_block = Block::default().style(Style::default().bg(app.theme.background));
+    frame.render_widget(bg_block, size);
+
+    let (header, strip, grid) = split_screen(size);
+    render_header(frame, header, app);
+    render_workspace_strip(frame, strip, app);
+    match app.view {
+        View::Grid => render_windows(frame, grid, app),
+        View::List => render_list(frame, grid, app),
+        View::Minimap => render_minimap(frame, grid, app),
+        View::Workspaces => render_workspaces(frame, grid, app),
+        View::Monitors => render_monitors(frame, grid, app),
+    }
+    if let Some(prompt) = &app.prompt {
+        render_prompt(frame, grid, app, prompt);
+    }
+    if let (true, Some(win)) = (app.details, app.shown(app.selected_index)) {
+        render_details(frame, grid, app, win);
+    }
+    if let Some(builder) = &app.rule_builder {
+        render_rule_builder(frame, grid, app, builder);
+    }
+    if let Some(pager) = &app.pager {
+        render_pager(frame, grid, app, pager);
+    }
+    if let Some(toast) = &app.toast {
+        render_toast(frame, grid, app, toast);
+    }
+}
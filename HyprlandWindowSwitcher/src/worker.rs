@@ -0,0 +1,145 @@
+// Background worker for compositor calls
+//
+// Dispatches and window-model refreshes run on a worker thread, one at a time
+// in the order they were submitted, so the UI keeps drawing and reading keys
+// while Hyprland is slow. Every call is bounded by backend::HYPRCTL_TIMEOUT and
+// its outcome comes back to the main loop as a message. Custom actions run
+// user commands that may take any time, so each gets a thread of its own.
+
+use std::io;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+
+use crate::actions;
+use crate::backend::{try_batch, try_dispatch, try_reload};
+use crate::daemon::Snapshot;
+
+pub enum Job {
+    /// One dispatcher with its arguments
+    Dispatch(Vec<String>),
+    /// `;`-separated `dispatch ...` commands run in one hyprctl call
+    Batch(String),
+    /// Reload the Hyprland config
+    Reload,
+    /// Only re-query the window model
+    Refresh,
+}
+
+impl Job {
+    pub fn dispatch(args: &[&str]) -> Self {
+        Job::Dispatch(args.iter().map(|arg| arg.to_string()).collect())
+    }
+}
+
+struct Request {
+    id: u64,
+    job: Job,
+    refresh: bool,
+}
+
+/// Outcome of a submitted job
+pub struct Done {
+    pub id: u64,
+    pub result: io::Result<()>,
+    // Window model queried after the job, when it asked for one
+    pub snapshot: Option<Snapshot>,
+    // What a command run with Worker::run printed
+    pub output: Option<String>,
+}
+
+type Report = Arc<dyn Fn(Done) + Send + Sync>;
+
+pub struct Worker {
+    requests: Option<Sender<Request>>,
+    // Sends a Done from any thread, for Worker::run
+    report: Option<Report>,
+    next_id: u64,
+}
+
+impl Worker {
+    /// Start the worker thread, reporting finished jobs on `results`
+    pub fn spawn<T: From<Done> + Send + 'static>(results: Sender<T>) -> Self {
+        let (requests, pending) = mpsc::channel::<Request>();
+        let report: Report = {
+            let results = results.clone();
+            Arc::new(move |done: Done| {
+                let _ = results.send(done.into());
+            })
+        };
+        thread::spawn(move || {
+            for request in pending {
+                let result = match &request.job {
+                    Job::Dispatch(args) => try_dispatch(args),
+                    Job::Batch(batch) => try_batch(batch),
+                    Job::Reload => try_reload(),
+                    Job::Refresh => Ok(()),
+                };
+                let (result, snapshot) = match (result, request.refresh) {
+                    (Ok(()), true) => match Snapshot::try_query() {
+                        Ok(snapshot) => (Ok(()), Some(snapshot)),
+                        Err(e) => (Err(e), None),
+                    },
+                    (result, _) => (result, None),
+                };
+                let done = Done {
+                    id: request.id,
+                    result,
+                    snapshot,
+                    output: None,
+                };
+                if results.send(done.into()).is_err() {
+                    break;
+                }
+            }
+        });
+        Worker {
+            requests: Some(requests),
+            report: Some(report),
+            next_id: 0,
+        }
+    }
+
+    /// A worker without a thread: jobs are accepted and never run, which
+    /// keeps tests and benchmarks away from the compositor
+    pub fn detached() -> Self {
+        Worker {
+            requests: None,
+            report: None,
+            next_id: 0,
+        }
+    }
+
+    /// Queue `job`, re-querying the window model afterwards when `refresh`
+    /// is set; returns the id its Done will carry
+    pub fn submit(&mut self, job: Job, refresh: bool) -> u64 {
+        self.next_id += 1;
+        if let Some(requests) = &self.requests {
+            let _ = requests.send(Request {
+                id: self.next_id,
+                job,
+                refresh,
+            });
+        }
+        self.next_id
+    }
+
+    /// Run a shell command on a thread of its own, without a time limit;
+    /// its Done carries the output
+    pub fn run(&mut self, command: String) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        if let Some(report) = self.report.clone() {
+            thread::spawn(move || {
+                let (result, output) = actions::run(&command);
+                report(Done {
+                    id,
+                    result,
+                    snapshot: None,
+                    output: Some(output),
+                });
+            });
+        }
+        id
+    }
+}
@@ -0,0 +1,398 @@
+// Snapshot tests for the rendered switcher, driven by a mock window list
+
+use std::io;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::backend::TestBackend;
+use ratatui::layout::Rect;
+use ratatui::Terminal;
+
+use hypr_window_switcher::app::{handle_key, handle_mouse, App, Options, Sort, SortColumn};
+use hypr_window_switcher::config::{Config, CustomAction, OutputMode, TitleRewrite};
+use hypr_window_switcher::daemon::Snapshot;
+use hypr_window_switcher::events::HyprEvent;
+use hypr_window_switcher::history::History;
+use hypr_window_switcher::marks::Marks;
+use hypr_window_switcher::model::{Monitor, Window, Workspace};
+use hypr_window_switcher::ui::draw;
+use hypr_window_switcher::worker::Done;
+
+fn window(n: usize, class: &str, title: &str, workspace_id: i64) -> Window {
+    Window {
+        size: (1280, 1440),
+        focus_history: n as i64,
+        ..Window::new(&format!("0x{:x}", 0x100 + n), class, title, workspace_id)
+    }
+}
+
+fn snapshot(windows: Vec<Window>) -> Snapshot {
+    let workspaces = (1..=2)
+        .map(|id| Workspace {
+            id,
+            name: id.to_string(),
+            monitor: "DP-1".to_string(),
+            windows: windows.iter().filter(|w| w.workspace_id == id).count(),
+        })
+        .collect();
+    Snapshot {
+        windows,
+        workspaces,
+        monitors: vec![Monitor {
+            focused: true,
+            ..Monitor::new(0, "DP-1", 2560, 1440)
+        }],
+        active_workspace: Some(1),
+        urgent: Vec::new(),
+    }
+}
+
+fn desktop() -> Vec<Window> {
+    vec![
+        window(0, "firefox", "Mozilla Firefox", 1),
+        window(1, "kitty", "~/src/hypr_window_switcher", 1),
+        window(2, "code", "main.rs - hypr_window_switcher - Visual Studio Code", 1),
+        window(3, "Slack", "(3) Slack | general", 2),
+        window(4, "spotify", "Spotify Premium", 2),
+    ]
+}
+
+// App over the mock windows with no saved marks or history, so nothing from
+// the machine running the tests leaks into the snapshots
+fn app(windows: Vec<Window>) -> App {
+    app_with(windows, &[])
+}
+
+fn app_with(windows: Vec<Window>, args: &[&str]) -> App {
+    let options = Options::from_args(args.iter().map(|arg| arg.to_string()));
+    App::with_state(
+        &options,
+        &Config::default(),
+        snapshot(windows),
+        None,
+        Marks::default(),
+        History::default(),
+    )
+}
+
+fn app_with_history(windows: Vec<Window>, history: History) -> App {
+    let options = Options::from_args(std::iter::empty());
+    App::with_state(&options, &Config::default(), snapshot(windows), None, Marks::default(), history)
+}
+
+fn render(app: &App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| draw(f, app)).unwrap();
+    terminal.backend().to_string()
+}
+
+fn press(app: &mut App, code: KeyCode, width: u16, height: u16) {
+    let key = KeyEvent::new(code, KeyModifiers::NONE);
+    handle_key(app, key, Rect::new(0, 0, width, height));
+}
+
+#[test]
+fn header_and_cards() {
+    insta::assert_snapshot!(render(&app(desktop()), 120, 34));
+}
+
+#[test]
+fn selection_follows_the_arrow_keys() {
+    let mut app = app(desktop());
+    press(&mut app, KeyCode::Right, 120, 34);
+    press(&mut app, KeyCode::Down, 120, 34);
+    insta::assert_snapshot!(render(&app, 120, 34));
+}
+
+#[test]
+fn empty_window_list() {
+    insta::assert_snapshot!(render(&app(Vec::new()), 120, 20));
+}
+
+#[test]
+fn narrow_terminal() {
+    insta::assert_snapshot!(render(&app(desktop()), 60, 40));
+}
+
+#[test]
+fn very_narrow_terminal() {
+    insta::assert_snapshot!(render(&app(desktop()), 30, 30));
+}
+
+#[test]
+fn pending_and_failed_actions() {
+    let mut app = app(desktop());
+    // Closing the first window stays pending, closing the second times out
+    press(&mut app, KeyCode::Char('x'), 120, 34);
+    press(&mut app, KeyCode::Right, 120, 34);
+    press(&mut app, KeyCode::Char('x'), 120, 34);
+    let timed_out = io::Error::new(io::ErrorKind::TimedOut, "hyprctl timed out");
+    assert!(app.job_done(Done {
+        id: 2,
+        result: Err(timed_out),
+        snapshot: None,
+        output: None,
+    }));
+    assert!(app.running);
+    insta::assert_snapshot!(render(&app, 120, 34));
+}
+
+#[test]
+fn tag_chips_and_tag_filter() {
+    let mut windows = desktop();
+    windows[1].tags = vec!["work".to_string()];
+    windows[2].tags = vec!["work".to_string(), "rust".to_string()];
+    let mut app = app(windows);
+    // Tagging kitty stays pending, there is no compositor to answer
+    press(&mut app, KeyCode::Right, 120, 34);
+    for code in [KeyCode::Char('t'), KeyCode::Char('+'), KeyCode::Char('x'), KeyCode::Enter] {
+        press(&mut app, code, 120, 34);
+    }
+    for code in [KeyCode::Char('/'), KeyCode::Char('#'), KeyCode::Char('w'), KeyCode::Char('o')] {
+        press(&mut app, code, 120, 34);
+    }
+    insta::assert_snapshot!(render(&app, 120, 34));
+}
+
+#[test]
+fn rule_builder_preview() {
+    let mut app = app(desktop());
+    app.rule_files = vec!["/home/me/.config/hypr/hyprland.conf".into()];
+    press(&mut app, KeyCode::Char('R'), 120, 34);
+    // Match by title, float, and a size typed over the current one
+    press(&mut app, KeyCode::Right, 120, 34);
+    press(&mut app, KeyCode::Down, 120, 34);
+    press(&mut app, KeyCode::Char(' '), 120, 34);
+    press(&mut app, KeyCode::Down, 120, 34);
+    for _ in 0.."1280 1440".len() {
+        press(&mut app, KeyCode::Backspace, 120, 34);
+    }
+    for c in "800 600".chars() {
+        press(&mut app, KeyCode::Char(c), 120, 34);
+    }
+    insta::assert_snapshot!(render(&app, 120, 34));
+}
+
+#[test]
+fn list_view_sorted_by_class() {
+    let mut app = app(desktop());
+    press(&mut app, KeyCode::Char('v'), 120, 20);
+    press(&mut app, KeyCode::Char('s'), 120, 20);
+    press(&mut app, KeyCode::Down, 120, 20);
+    press(&mut app, KeyCode::Char('x'), 120, 20);
+    insta::assert_snapshot!(render(&app, 120, 20));
+}
+
+#[test]
+fn clicking_a_list_header_sorts_and_reverses() {
+    let mut app = app(desktop());
+    press(&mut app, KeyCode::Char('v'), 120, 20);
+    // The header line sits under the title and the workspace strip
+    let click = MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 5,
+        row: 7,
+        modifiers: KeyModifiers::NONE,
+    };
+    handle_mouse(&mut app, click, Rect::new(0, 0, 120, 20));
+    handle_mouse(&mut app, click, Rect::new(0, 0, 120, 20));
+    let descending = Sort {
+        column: SortColumn::Class,
+        descending: true,
+    };
+    assert_eq!(app.sort, Some(descending));
+    let classes: Vec<&str> = (0..5).map(|i| app.shown(i).unwrap().class.as_str()).collect();
+    assert_eq!(classes, ["spotify", "Slack", "kitty", "firefox", "code"]);
+    handle_mouse(&mut app, click, Rect::new(0, 0, 120, 20));
+    assert_eq!(app.sort, None);
+}
+
+#[test]
+fn inline_list() {
+    let mut app = app_with(desktop(), &["--height", "4"]);
+    // Typing filters straight away and the list scrolls with the selection
+    press(&mut app, KeyCode::Char('i'), 80, 4);
+    press(&mut app, KeyCode::Down, 80, 4);
+    press(&mut app, KeyCode::Down, 80, 4);
+    insta::assert_snapshot!(render(&app, 80, 4));
+}
+
+fn custom_action(key: &str, name: &str, output: OutputMode) -> CustomAction {
+    CustomAction {
+        key: key.to_string(),
+        name: name.to_string(),
+        command: "echo {class}".to_string(),
+        output,
+    }
+}
+
+#[test]
+fn custom_action_on_picked_windows_fills_the_pager() {
+    let mut app = app(desktop());
+    app.custom_actions = vec![custom_action("E", "echo class", OutputMode::Pager)];
+    // Pick the first two cards and run the action on both
+    press(&mut app, KeyCode::Char(' '), 120, 34);
+    press(&mut app, KeyCode::Right, 120, 34);
+    press(&mut app, KeyCode::Char(' '), 120, 34);
+    assert_eq!(app.picked.len(), 2);
+    press(&mut app, KeyCode::Char('E'), 120, 34);
+    for (id, output) in [(1, "firefox\n"), (2, "kitty\n")] {
+        app.job_done(Done {
+            id,
+            result: Ok(()),
+            snapshot: None,
+            output: Some(output.to_string()),
+        });
+    }
+    insta::assert_snapshot!(render(&app, 120, 34));
+    press(&mut app, KeyCode::Esc, 120, 34);
+    assert!(app.pager.is_none());
+    assert!(app.running);
+}
+
+#[test]
+fn custom_action_result_as_a_toast() {
+    let mut app = app(desktop());
+    app.custom_actions = vec![custom_action("ctrl+e", "echo class", OutputMode::Toast)];
+    handle_key(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+        Rect::new(0, 0, 120, 34),
+    );
+    app.job_done(Done {
+        id: 1,
+        result: Err(io::Error::other("exit status 1")),
+        snapshot: None,
+        output: Some("firefox\nno such file\n\n".to_string()),
+    });
+    assert_eq!(app.toast, Some(Err("echo class: no such file (exit status 1)".to_string())));
+    insta::assert_snapshot!(render(&app, 120, 34));
+    // Any key clears it
+    press(&mut app, KeyCode::Down, 120, 34);
+    assert_eq!(app.toast, None);
+}
+
+#[test]
+fn unreadable_window_list_is_reported() {
+    let mut app = app(Vec::new());
+    let timed_out = io::Error::new(io::ErrorKind::TimedOut, "hyprctl clients -j timed out");
+    app.snapshot_failed(&timed_out);
+    assert!(app.running);
+    let screen = render(&app, 120, 20);
+    assert!(screen.contains("Could not read the window list: hyprctl clients -j timed out"));
+}
+
+#[test]
+fn peek_pins_a_floating_host_from_the_snapshot() {
+    let mut windows = desktop();
+    let mut host = window(9, "kitty", "hypr_window_switcher", 1);
+    host.floating = true;
+    windows.push(host.clone());
+    let mut app = App::with_state(
+        &Options::from_args(std::iter::empty()),
+        &Config::default(),
+        snapshot(windows),
+        Some(host.id.clone()),
+        Marks::default(),
+        History::default(),
+    );
+    // Pinning and unpinning go through the worker, no hyprctl is run here
+    app.set_peek(true);
+    assert!(app.pinned_host);
+    assert!(app.unpin_host().is_some());
+    assert!(!app.pinned_host);
+    assert_eq!(app.unpin_host(), None);
+}
+
+#[test]
+fn inline_cancel_leaves_focus_alone() {
+    let mut app = app_with(desktop(), &["--height", "4"]);
+    assert!(app.original.is_some());
+    press(&mut app, KeyCode::Esc, 80, 4);
+    // Quits at once instead of waiting on a focuswindow job
+    assert!(!app.running);
+    assert_eq!(app.chosen, None);
+}
+
+#[test]
+fn frecent_windows_are_listed_first() {
+    let mut history = History::default();
+    history.record("spotify", "Spotify Premium");
+    history.record("code", "main.rs - hypr_window_switcher - Visual Studio Code");
+    history.record("code", "main.rs - hypr_window_switcher - Visual Studio Code");
+    let app = app(desktop());
+    let hyprland_order: Vec<&str> = (0..5).map(|i| app.shown(i).unwrap().class.as_str()).collect();
+    assert_eq!(hyprland_order, ["firefox", "kitty", "code", "Slack", "spotify"]);
+
+    let app = app_with_history(desktop(), history);
+    // Unused windows keep Hyprland's order behind the ranked ones
+    let ranked: Vec<&str> = (0..5).map(|i| app.shown(i).unwrap().class.as_str()).collect();
+    assert_eq!(ranked, ["code", "spotify", "firefox", "kitty", "Slack"]);
+}
+
+#[test]
+fn tags_with_batch_separators_are_rejected() {
+    let mut app = app(desktop());
+    press(&mut app, KeyCode::Char('t'), 120, 34);
+    for c in "a;dispatch exit".chars() {
+        press(&mut app, KeyCode::Char(c), 120, 34);
+    }
+    press(&mut app, KeyCode::Enter, 120, 34);
+    assert!(render(&app, 120, 34).contains("invalid tag"));
+}
+
+#[test]
+fn writing_a_rule_names_the_backup() {
+    let dir = hypr_window_switcher::scratch_dir("ui-rule");
+    let config = dir.join("hyprland.conf");
+    std::fs::write(&config, "# config\n").unwrap();
+    let mut app = app(desktop());
+    app.rule_files = vec![config.clone()];
+    app.reload_rules = false;
+    press(&mut app, KeyCode::Char('R'), 120, 34);
+    press(&mut app, KeyCode::Down, 120, 34);
+    press(&mut app, KeyCode::Char(' '), 120, 34);
+    press(&mut app, KeyCode::Enter, 120, 34);
+    let Some(Ok(toast)) = &app.toast else {
+        panic!("no toast after writing the rule: {:?}", app.toast);
+    };
+    let backup = toast.split("backup in ").nth(1).expect("backup path in the toast");
+    assert_eq!(std::fs::read_to_string(backup).unwrap(), "# config\n");
+    assert!(std::fs::read_to_string(&config).unwrap().ends_with("windowrule = float, class:^(firefox)$\n"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn config_problems_show_once_the_screen_is_up() {
+    let mut config = Config::default();
+    config.warnings.push("ignoring invalid config config.toml: expected `=`".to_string());
+    config.rewrites.push(TitleRewrite {
+        class: None,
+        pattern: "(unclosed".to_string(),
+        replace: String::new(),
+    });
+    let options = Options::from_args(std::iter::empty());
+    let app = App::with_state(&options, &config, snapshot(desktop()), None, Marks::default(), History::default());
+    let Some(Err(toast)) = &app.toast else {
+        panic!("no warning toast: {:?}", app.toast);
+    };
+    assert!(toast.starts_with("ignoring invalid config config.toml"));
+    assert!(toast.contains("ignoring title rewrite \"(unclosed\""));
+}
+
+#[test]
+fn focusing_an_urgent_window_clears_its_badge() {
+    let mut app = app_with(desktop(), &["--urgent-first", "--stay-open"]);
+    let event = |name: &str, data: &str| HyprEvent {
+        name: name.to_string(),
+        data: data.to_string(),
+    };
+    // Events carry addresses without the 0x prefix
+    assert!(app.handle_event(&event("urgent", "104")));
+    assert_eq!(app.shown(1).unwrap().class, "spotify");
+    assert!(app.handle_event(&event("activewindowv2", "104")));
+    assert!(app.urgent.is_empty());
+    // Slack keeps its unread counter, Spotify drops back into place
+    assert_eq!(app.shown(0).unwrap().class, "Slack");
+    assert_ne!(app.shown(1).unwrap().class, "spotify");
+    assert!(!app.handle_event(&event("activewindowv2", "101")));
+}